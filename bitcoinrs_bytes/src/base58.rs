@@ -0,0 +1,154 @@
+use std::error::Error;
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Maximum length of string `decode` accepts.
+/// Decoding takes quadratic time, so untrusted long strings are rejected up front.
+/// Extended keys, the longest Base58Check strings in use, have 111 characters.
+pub const MAX_LENGTH: usize = 128;
+
+/// Encode bytes into Base58 string.
+/// Each leading zero byte is encoded as '1'.
+pub fn encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+
+    // Base58 digits in little endian. log(256) / log(58) < 1.37
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 137 / 100 + 1);
+    for byte in &bytes[zeros..] {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut s = String::with_capacity(zeros + digits.len());
+    for _ in 0..zeros {
+        s.push('1');
+    }
+    for digit in digits.iter().rev() {
+        s.push(ALPHABET[*digit as usize] as char);
+    }
+    s
+}
+
+/// Decode Base58 string into bytes.
+/// Strings longer than `MAX_LENGTH` are rejected.
+pub fn decode(s: &str) -> Result<Vec<u8>, Base58Error> {
+    if s.len() > MAX_LENGTH {
+        return Err(Base58Error::TooLong(s.len()));
+    }
+    let zeros = s.bytes().take_while(|c| *c == b'1').count();
+
+    // Bytes in little endian. log(58) / log(256) < 0.74
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len() * 74 / 100 + 1);
+    for (index, c) in s.char_indices().skip(zeros) {
+        let mut carry = digit_value(c).ok_or(Base58Error::InvalidChar { c, index })?;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    bytes.extend((0..zeros).map(|_| 0));
+    bytes.reverse();
+    Ok(bytes)
+}
+
+fn digit_value(c: char) -> Option<u32> {
+    if !c.is_ascii() {
+        return None;
+    }
+    ALPHABET
+        .iter()
+        .position(|a| *a == c as u8)
+        .map(|p| p as u32)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base58Error {
+    /// `c` at byte `index` is not in the Base58 alphabet.
+    InvalidChar { c: char, index: usize },
+    /// Length of string is longer than `MAX_LENGTH`.
+    TooLong(usize),
+}
+
+impl Display for Base58Error {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            Base58Error::InvalidChar { c, index } => {
+                write!(f, "invalid base58 character {:?} at {}", c, index)
+            }
+            Base58Error::TooLong(len) => write!(f, "length {} is too long", len),
+        }
+    }
+}
+
+impl Error for Base58Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex;
+
+    const VECTORS: &[(&str, &str)] = &[
+        ("", ""),
+        ("61", "2g"),
+        ("626262", "a3gV"),
+        ("636363", "aPEr"),
+        (
+            "73696d706c792061206c6f6e6720737472696e67",
+            "2cFupjhnEsSn59qHXstmK2ffpLv2",
+        ),
+        (
+            "00eb15231dfceb60925886b67d065299925915aeb172c06647",
+            "1NS17iag9jJgTHD1VXjvLCEnZuQ3rJDE9L",
+        ),
+        ("516b6fcd0f", "ABnLTmg"),
+        ("bf4f89001e670274dd", "3SEo3LWLoPntC"),
+        ("572e4794", "3EFU7m"),
+        ("ecac89cad93923c02321", "EJDM8drfXA6uyA"),
+        ("10c8511e", "Rt5zm"),
+        ("00000000000000000000", "1111111111"),
+    ];
+
+    #[test]
+    fn encode_decode() {
+        for &(h, b58) in VECTORS {
+            let bytes = hex::decode(h).unwrap();
+            assert_eq!(encode(&bytes), b58);
+            assert_eq!(decode(b58).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn invalid_char() {
+        assert_eq!(
+            decode("1110I"),
+            Err(Base58Error::InvalidChar { c: '0', index: 3 })
+        );
+        assert_eq!(
+            decode("2gé"),
+            Err(Base58Error::InvalidChar { c: 'é', index: 2 })
+        );
+    }
+
+    #[test]
+    fn too_long() {
+        let s = "z".repeat(MAX_LENGTH);
+        assert_eq!(encode(&decode(&s).unwrap()), s);
+        let s = "z".repeat(MAX_LENGTH + 1);
+        assert_eq!(decode(&s), Err(Base58Error::TooLong(MAX_LENGTH + 1)));
+    }
+}
@@ -0,0 +1,386 @@
+use std::error::Error;
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+const CHECKSUM_SIZE: usize = 6;
+
+/// Maximum length of a whole string.
+const MAX_LENGTH: usize = 90;
+
+/// Checksum algorithm. Segwit version 0 uses `Bech32` (BIP173),
+/// and version 1 and above use `Bech32m` (BIP350).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(&self) -> u32 {
+        match *self {
+            Variant::Bech32 => 1,
+            Variant::Bech32m => 0x2bc830a3,
+        }
+    }
+
+    fn from_constant(c: u32) -> Option<Variant> {
+        match c {
+            1 => Some(Variant::Bech32),
+            0x2bc830a3 => Some(Variant::Bech32m),
+            _ => None,
+        }
+    }
+}
+
+/// Encode human readable part and 5 bits values into lower case string.
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> Result<String, Bech32Error> {
+    check_hrp(hrp)?;
+    let hrp = hrp.to_ascii_lowercase();
+    if let Some(p) = data.iter().position(|d| *d >= 32) {
+        return Err(Bech32Error::InvalidData(p));
+    }
+    let len = hrp.len() + 1 + data.len() + CHECKSUM_SIZE;
+    if len > MAX_LENGTH {
+        return Err(Bech32Error::TooLong(len));
+    }
+
+    let checksum = create_checksum(&hrp, data, variant);
+    let mut s = String::with_capacity(len);
+    s.push_str(&hrp);
+    s.push('1');
+    for d in data.iter().chain(checksum.iter()) {
+        s.push(CHARSET[*d as usize] as char);
+    }
+    Ok(s)
+}
+
+/// Decode string into lower case human readable part and 5 bits values.
+pub fn decode(s: &str) -> Result<(String, Vec<u8>, Variant), Bech32Error> {
+    if s.len() > MAX_LENGTH {
+        return Err(Bech32Error::TooLong(s.len()));
+    }
+    check_case(s)?;
+    let s = s.to_ascii_lowercase();
+
+    let sep = s.rfind('1').ok_or(Bech32Error::MissingSeparator)?;
+    if sep == 0 || s.len() - sep - 1 < CHECKSUM_SIZE {
+        return Err(Bech32Error::MissingSeparator);
+    }
+    let hrp = &s[..sep];
+    check_hrp(hrp)?;
+
+    let mut data = Vec::with_capacity(s.len() - sep - 1);
+    for (i, c) in s[sep + 1..].char_indices() {
+        let index = sep + 1 + i;
+        match CHARSET.iter().position(|x| *x as char == c) {
+            Some(d) => data.push(d as u8),
+            None => return Err(Bech32Error::InvalidChar { c, index }),
+        }
+    }
+
+    let variant =
+        Variant::from_constant(polymod(hrp, &data)).ok_or(Bech32Error::InvalidChecksum)?;
+    data.truncate(data.len() - CHECKSUM_SIZE);
+    Ok((hrp.to_string(), data, variant))
+}
+
+/// Regroup bits of `data` from `from` bits per value into `to` bits per value.
+/// If `pad` is false, leftover bits must be less than `from` bits and zero.
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Bech32Error> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1 << to) - 1;
+    let mut ret = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for (i, value) in data.iter().enumerate() {
+        if (*value as u32) >> from != 0 {
+            return Err(Bech32Error::InvalidData(i));
+        }
+        acc = (acc << from) | *value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            ret.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            ret.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & max) != 0 {
+        return Err(Bech32Error::InvalidPadding);
+    }
+    Ok(ret)
+}
+
+/// Encode segwit address.
+pub fn encode_segwit_address(
+    hrp: &str,
+    version: u8,
+    program: &[u8],
+) -> Result<String, Bech32Error> {
+    check_witness_program(version, program)?;
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+    let variant = if version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    };
+    encode(hrp, &data, variant)
+}
+
+/// Decode segwit address into witness version and witness program.
+/// Human readable part must be `hrp`.
+pub fn decode_segwit_address(hrp: &str, addr: &str) -> Result<(u8, Vec<u8>), Bech32Error> {
+    let (decoded_hrp, data, variant) = decode(addr)?;
+    if decoded_hrp != hrp.to_ascii_lowercase() {
+        return Err(Bech32Error::UnexpectedHrp(decoded_hrp));
+    }
+    if data.is_empty() {
+        return Err(Bech32Error::InvalidWitnessVersion(None));
+    }
+
+    let version = data[0];
+    let program = convert_bits(&data[1..], 5, 8, false)?;
+    check_witness_program(version, &program)?;
+
+    let expected = if version == 0 {
+        Variant::Bech32
+    } else {
+        Variant::Bech32m
+    };
+    if variant != expected {
+        return Err(Bech32Error::InvalidVariant(variant));
+    }
+    Ok((version, program))
+}
+
+fn check_witness_program(version: u8, program: &[u8]) -> Result<(), Bech32Error> {
+    if version > 16 {
+        return Err(Bech32Error::InvalidWitnessVersion(Some(version)));
+    }
+    let len = program.len();
+    if !(2..=40).contains(&len) || (version == 0 && len != 20 && len != 32) {
+        return Err(Bech32Error::InvalidProgramLength(len));
+    }
+    Ok(())
+}
+
+/// Human readable part consists of 1 to 83 ascii characters in range 33 to 126.
+fn check_hrp(hrp: &str) -> Result<(), Bech32Error> {
+    if hrp.is_empty() || hrp.len() > 83 {
+        return Err(Bech32Error::InvalidHrpLength(hrp.len()));
+    }
+    for (index, c) in hrp.char_indices() {
+        if !(33..=126).contains(&(c as u32)) {
+            return Err(Bech32Error::InvalidChar { c, index });
+        }
+    }
+    Ok(())
+}
+
+fn check_case(s: &str) -> Result<(), Bech32Error> {
+    let first_lower = s.bytes().position(|c| c.is_ascii_lowercase());
+    let first_upper = s.bytes().position(|c| c.is_ascii_uppercase());
+    match (first_lower, first_upper) {
+        (Some(l), Some(u)) => Err(Bech32Error::MixedCase(l.max(u))),
+        _ => Ok(()),
+    }
+}
+
+fn polymod_step(chk: u32, value: u8) -> u32 {
+    let top = chk >> 25;
+    let mut chk = ((chk & 0x1ffffff) << 5) ^ value as u32;
+    for (i, g) in GENERATOR.iter().enumerate() {
+        if (top >> i) & 1 == 1 {
+            chk ^= g;
+        }
+    }
+    chk
+}
+
+fn polymod(hrp: &str, data: &[u8]) -> u32 {
+    let mut chk = 1;
+    for c in hrp.bytes() {
+        chk = polymod_step(chk, c >> 5);
+    }
+    chk = polymod_step(chk, 0);
+    for c in hrp.bytes() {
+        chk = polymod_step(chk, c & 0x1f);
+    }
+    for d in data {
+        chk = polymod_step(chk, *d);
+    }
+    chk
+}
+
+fn create_checksum(hrp: &str, data: &[u8], variant: Variant) -> [u8; CHECKSUM_SIZE] {
+    let mut values = data.to_vec();
+    values.extend_from_slice(&[0; CHECKSUM_SIZE]);
+    let m = polymod(hrp, &values) ^ variant.constant();
+
+    let mut checksum = [0; CHECKSUM_SIZE];
+    for (i, c) in checksum.iter_mut().enumerate() {
+        *c = ((m >> (5 * (5 - i))) & 0x1f) as u8;
+    }
+    checksum
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bech32Error {
+    /// `c` at byte offset `index` is not allowed.
+    InvalidChar { c: char, index: usize },
+    /// Both of upper and lower case are used. Index of the first character of later case.
+    MixedCase(usize),
+    /// No separator '1', or data part is shorter than checksum.
+    MissingSeparator,
+    InvalidHrpLength(usize),
+    TooLong(usize),
+    InvalidChecksum,
+    /// Value at the index does not fit in expected bits.
+    InvalidData(usize),
+    /// Non zero padding, or padding longer than necessary.
+    InvalidPadding,
+    UnexpectedHrp(String),
+    /// `None` if witness version is missing.
+    InvalidWitnessVersion(Option<u8>),
+    InvalidProgramLength(usize),
+    /// Checksum variant does not match witness version.
+    InvalidVariant(Variant),
+}
+
+impl Display for Bech32Error {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            Bech32Error::InvalidChar { c, index } => {
+                write!(f, "invalid character {:?} at {}", c, index)
+            }
+            Bech32Error::MixedCase(index) => write!(f, "mixed case at {}", index),
+            Bech32Error::MissingSeparator => write!(f, "missing separator"),
+            Bech32Error::InvalidHrpLength(len) => {
+                write!(f, "invalid length {} of human readable part", len)
+            }
+            Bech32Error::TooLong(len) => write!(f, "length {} is too long", len),
+            Bech32Error::InvalidChecksum => write!(f, "invalid checksum"),
+            Bech32Error::InvalidData(index) => write!(f, "invalid data at {}", index),
+            Bech32Error::InvalidPadding => write!(f, "invalid padding"),
+            Bech32Error::UnexpectedHrp(ref hrp) => {
+                write!(f, "unexpected human readable part {:?}", hrp)
+            }
+            Bech32Error::InvalidWitnessVersion(Some(v)) => {
+                write!(f, "invalid witness version {}", v)
+            }
+            Bech32Error::InvalidWitnessVersion(None) => write!(f, "missing witness version"),
+            Bech32Error::InvalidProgramLength(len) => {
+                write!(f, "invalid witness program length {}", len)
+            }
+            Bech32Error::InvalidVariant(variant) => {
+                write!(f, "{:?} is not allowed for this witness version", variant)
+            }
+        }
+    }
+}
+
+impl Error for Bech32Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex;
+
+    /// From BIP173 and BIP350. Witness program is shown with its version and length byte.
+    const VALID_ADDRESSES: &[(&str, &str)] = &[
+        (
+            "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+            "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+        ),
+        (
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+            "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+        ),
+        (
+            "bc1pw508d6qejxtdg4y5r3zarvary0c5xw7kw508d6qejxtdg4y5r3zarvary0c5xw7kt5nd6y",
+            "5128751e76e8199196d454941c45d1b3a323f1433bd6751e76e8199196d454941c45d1b3a323f1433bd6",
+        ),
+        ("BC1SW50QGDZ25J", "6002751e"),
+        (
+            "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcs",
+            "5210751e76e8199196d454941c45d1b3a323",
+        ),
+        (
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+            "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        ),
+    ];
+
+    #[test]
+    fn valid_segwit_addresses() {
+        for &(addr, script) in VALID_ADDRESSES {
+            let hrp = &addr[..2];
+            let (version, program) = decode_segwit_address(hrp, addr).unwrap();
+            let script = hex::decode(script).unwrap();
+            let expected_version = if script[0] == 0 { 0 } else { script[0] - 0x50 };
+            assert_eq!(version, expected_version);
+            assert_eq!(program, &script[2..]);
+
+            let encoded = encode_segwit_address(hrp, version, &program).unwrap();
+            assert_eq!(encoded, addr.to_ascii_lowercase());
+        }
+    }
+
+    #[test]
+    fn invalid_segwit_addresses() {
+        // Bech32m checksum for version 0.
+        assert_eq!(
+            decode_segwit_address("bc", "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh"),
+            Err(Bech32Error::InvalidVariant(Variant::Bech32m))
+        );
+        // Bech32 checksum for version 1.
+        assert_eq!(
+            decode_segwit_address(
+                "bc",
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd"
+            ),
+            Err(Bech32Error::InvalidVariant(Variant::Bech32))
+        );
+        assert_eq!(
+            decode_segwit_address("tb", "BC1SW50QGDZ25J"),
+            Err(Bech32Error::UnexpectedHrp("bc".to_string()))
+        );
+        assert_eq!(
+            decode_segwit_address("bc", "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcS"),
+            Err(Bech32Error::MixedCase(35))
+        );
+        assert_eq!(
+            decode_segwit_address("bc", "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcb"),
+            Err(Bech32Error::InvalidChar { c: 'b', index: 35 })
+        );
+        assert_eq!(
+            decode("bé1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"),
+            Err(Bech32Error::InvalidChar { c: 'é', index: 1 })
+        );
+        assert_eq!(
+            decode("bc1qw508d6qejxtdg4y5r3zarvary0c5€xw7kv8f3t4"),
+            Err(Bech32Error::InvalidChar { c: '€', index: 32 })
+        );
+        assert_eq!(
+            encode("bé", &[0], Variant::Bech32),
+            Err(Bech32Error::InvalidChar { c: 'é', index: 1 })
+        );
+        assert_eq!(
+            decode_segwit_address("bc", "bc1zw508d6qejxtdg4y5r3zarvaryvaxxpcq"),
+            Err(Bech32Error::InvalidChecksum)
+        );
+        assert_eq!(
+            encode_segwit_address("bc", 0, &[0; 21]),
+            Err(Bech32Error::InvalidProgramLength(21))
+        );
+        assert_eq!(
+            encode_segwit_address("bc", 17, &[0; 20]),
+            Err(Bech32Error::InvalidWitnessVersion(Some(17)))
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use decode::{DecodeError, DecodeErrorKind, ReadBuffer};
+use encode::WriteBuffer;
+
+/// Write bits into underlying `WriteBuffer`, most significant bit first.
+/// Bits are written to the buffer byte by byte, so call `finish` after the last write.
+/// Dropping a writer with a partial byte pending is a bug, and panics in debug builds.
+pub struct BitWriter<'w, W: 'w> {
+    buf: &'w mut W,
+    byte: u8,
+    /// Number of bits used in `byte`.
+    bits: u8,
+}
+
+impl<'w, W: WriteBuffer> BitWriter<'w, W> {
+    pub fn new(buf: &'w mut W) -> BitWriter<'w, W> {
+        BitWriter {
+            buf,
+            byte: 0,
+            bits: 0,
+        }
+    }
+
+    /// Write lower `nbits` bits of `value`.
+    ///
+    /// # Panic
+    /// when `nbits` is larger than 64.
+    pub fn write_bits(&mut self, value: u64, nbits: u8) {
+        assert!(nbits <= 64);
+
+        let mut remaining = nbits;
+        while remaining > 0 {
+            let n = (8 - self.bits).min(remaining);
+            let chunk = ((value >> (remaining - n)) & ((1 << n) - 1)) as u8;
+            self.byte |= chunk << (8 - self.bits - n);
+            self.bits += n;
+            remaining -= n;
+
+            if self.bits == 8 {
+                self.buf.write(self.byte);
+                self.byte = 0;
+                self.bits = 0;
+            }
+        }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        self.write_bits(bit as u64, 1);
+    }
+
+    /// Write `x` in Golomb-Rice coding with parameter `p`;
+    /// quotient `x >> p` in unary followed by lower `p` bits of `x`.
+    ///
+    /// # Panic
+    /// when `p` is larger than 63.
+    pub fn write_golomb_rice(&mut self, p: u8, x: u64) {
+        assert!(p < 64);
+
+        let mut q = x >> p;
+        while q > 0 {
+            let n = q.min(64);
+            self.write_bits(!0, n as u8);
+            q -= n;
+        }
+        self.write_bit(false);
+        self.write_bits(x, p);
+    }
+
+    /// Write remaining bits padded with zeros.
+    pub fn finish(mut self) {
+        if self.bits > 0 {
+            self.buf.write(self.byte);
+            self.byte = 0;
+            self.bits = 0;
+        }
+    }
+}
+
+impl<'w, W> Drop for BitWriter<'w, W> {
+    fn drop(&mut self) {
+        if !::std::thread::panicking() {
+            debug_assert!(self.bits == 0, "BitWriter dropped without finish");
+        }
+    }
+}
+
+/// Read bits from underlying `ReadBuffer`, most significant bit first.
+pub struct BitReader<'r, R: 'r> {
+    buf: &'r mut R,
+    byte: u8,
+    /// Number of bits not read yet in `byte`.
+    bits: u8,
+}
+
+impl<'r, R: ReadBuffer> BitReader<'r, R> {
+    pub fn new(buf: &'r mut R) -> BitReader<'r, R> {
+        BitReader {
+            buf,
+            byte: 0,
+            bits: 0,
+        }
+    }
+
+    /// Read `nbits` bits as lower bits of returned value.
+    ///
+    /// # Panic
+    /// when `nbits` is larger than 64.
+    pub fn read_bits(&mut self, nbits: u8) -> Result<u64, DecodeError> {
+        assert!(nbits <= 64);
+
+        let mut value: u64 = 0;
+        let mut remaining = nbits;
+        while remaining > 0 {
+            if self.bits == 0 {
+                self.byte = self.buf.read::<u8>()?;
+                self.bits = 8;
+            }
+            let n = self.bits.min(remaining);
+            let chunk = (self.byte >> (self.bits - n)) & ((1u16 << n) - 1) as u8;
+            value = value.checked_shl(n as u32).unwrap_or(0) | chunk as u64;
+            self.bits -= n;
+            remaining -= n;
+        }
+        Ok(value)
+    }
+
+    pub fn read_bit(&mut self) -> Result<bool, DecodeError> {
+        Ok(self.read_bits(1)? == 1)
+    }
+
+    /// Read a value in Golomb-Rice coding with parameter `p`.
+    /// Fails with `InvalidBytes` when the quotient does not fit in `u64`.
+    ///
+    /// # Panic
+    /// when `p` is larger than 63.
+    pub fn read_golomb_rice(&mut self, p: u8) -> Result<u64, DecodeError> {
+        assert!(p < 64);
+
+        let max_q = u64::MAX >> p;
+        let mut q: u64 = 0;
+        while self.read_bit()? {
+            if q == max_q {
+                let offset = self.buf.offset();
+                return Err(DecodeError::new(DecodeErrorKind::InvalidBytes).at(offset));
+            }
+            q += 1;
+        }
+        let r = self.read_bits(p)?;
+        Ok((q << p) + r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn bits_roundtrip() {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = BitWriter::new(&mut bytes);
+            writer.write_bits(0b101, 3);
+            writer.write_bits(0xABCD, 16);
+            writer.write_bits(u64::MAX, 64);
+            writer.write_bit(true);
+            writer.finish();
+        }
+        assert_eq!(bytes.len(), 11);
+        assert_eq!(&bytes[..3], &[0b1011_0101, 0b0111_1001, 0b1011_1111]);
+
+        let mut buf = Cursor::new(bytes.as_slice());
+        let mut reader = BitReader::new(&mut buf);
+        assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+        assert_eq!(reader.read_bits(16).unwrap(), 0xABCD);
+        assert_eq!(reader.read_bits(64).unwrap(), u64::MAX);
+        assert!(reader.read_bit().unwrap());
+        assert_eq!(reader.read_bits(4).unwrap(), 0);
+        assert!(reader.read_bit().is_err());
+    }
+
+    #[test]
+    fn golomb_rice() {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = BitWriter::new(&mut bytes);
+            // q = 1, r = 1
+            writer.write_golomb_rice(2, 5);
+            writer.finish();
+        }
+        assert_eq!(bytes, [0b1001_0000]);
+
+        let values = [0, 1, 524_287, 524_288, 1 << 30, 12_345_678_901];
+        let mut bytes = Vec::new();
+        {
+            let mut writer = BitWriter::new(&mut bytes);
+            for v in values.iter() {
+                writer.write_golomb_rice(19, *v);
+            }
+            writer.finish();
+        }
+        let mut buf = Cursor::new(bytes.as_slice());
+        let mut reader = BitReader::new(&mut buf);
+        for v in values.iter() {
+            assert_eq!(reader.read_golomb_rice(19).unwrap(), *v);
+        }
+    }
+
+    #[test]
+    fn golomb_rice_quotient_overflow() {
+        // Quotient of p = 60 is at most 15.
+        let bytes = [0b1111_1111, 0b1111_1111];
+        let mut buf = Cursor::new(&bytes[..]);
+        let e = BitReader::new(&mut buf).read_golomb_rice(60).unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::InvalidBytes);
+        assert_eq!(e.offset(), Some(2));
+
+        let bytes = [0b1111_1111, 0b1110_0000, 0, 0, 0, 0, 0, 0, 0];
+        let mut buf = Cursor::new(&bytes[..]);
+        let x = BitReader::new(&mut buf).read_golomb_rice(60).unwrap();
+        assert_eq!(x, 11 << 60);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "BitWriter dropped without finish")]
+    fn drop_without_finish() {
+        let mut bytes = Vec::new();
+        BitWriter::new(&mut bytes).write_bit(true);
+    }
+
+    #[test]
+    #[should_panic]
+    fn golomb_rice_large_parameter() {
+        let mut bytes = Vec::new();
+        BitWriter::new(&mut bytes).write_golomb_rice(64, 0);
+    }
+}
@@ -1,3 +1,5 @@
+use std::io::{Error as IoError, IoSliceMut, Read};
+
 use encode::WriteBuffer;
 
 pub struct Buffer {
@@ -39,6 +41,18 @@ impl WriteBuffer for Buffer {
     fn has_buffer(&self, _size: usize) -> bool {
         true
     }
+
+    fn written_mut(&mut self, len: usize) -> Option<&mut [u8]> {
+        let start = self.bytes.len().checked_sub(len)?;
+        if start < self.discarded {
+            return None;
+        }
+        Some(&mut self.bytes[start..])
+    }
+
+    fn keeps_written(&self, _len: usize) -> bool {
+        true
+    }
 }
 
 impl AsRef<[u8]> for Buffer {
@@ -47,6 +61,161 @@ impl AsRef<[u8]> for Buffer {
     }
 }
 
+/// Capacity limited ring buffer.
+///
+/// Unlike `Buffer`, it never grows. `has_buffer` tells whether incoming bytes fit,
+/// so a reader can stop reading from a socket until some bytes are consumed.
+///
+/// Unread bytes wrap around the end of the storage and are never moved by writes or reads,
+/// so they are exposed as two slices by `as_slices`.
+/// Use `make_contiguous` when a single slice is required.
+pub struct RingBuffer {
+    bytes: Box<[u8]>,
+    /// Index of the first unread byte.
+    head: usize,
+    /// Length of unread bytes.
+    len: usize,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> RingBuffer {
+        RingBuffer {
+            bytes: vec![0; capacity].into_boxed_slice(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Length of unread bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of bytes which can be written before this buffer gets full.
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.len
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    pub fn drop_front(&mut self, size: usize) {
+        assert!(size <= self.len);
+
+        self.head = self.wrap(self.head + size);
+        self.len -= size;
+        if self.len == 0 {
+            // Rewind for free so that following bytes are less likely to wrap.
+            self.head = 0;
+        }
+    }
+
+    /// Unread bytes; the second slice is not empty when they wrap around the end of storage.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let end = self.head + self.len;
+        if end <= self.capacity() {
+            (&self.bytes[self.head..end], &[])
+        } else {
+            (&self.bytes[self.head..], &self.bytes[..end - self.capacity()])
+        }
+    }
+
+    /// Unread bytes as a single slice.
+    /// When they wrap around the end of storage, the storage is rotated first,
+    /// which moves every byte of it.
+    pub fn make_contiguous(&mut self) -> &mut [u8] {
+        if self.head + self.len > self.capacity() {
+            self.bytes.rotate_left(self.head);
+            self.head = 0;
+        }
+        &mut self.bytes[self.head..self.head + self.len]
+    }
+
+    /// Read bytes from `reader` into free space of this buffer, including space before
+    /// the first unread byte. Returns `Ok(0)` without reading when this buffer is full;
+    /// use `is_full` to tell it from EOF.
+    pub fn read_from<R: Read>(&mut self, reader: &mut R) -> Result<usize, IoError> {
+        if self.is_full() {
+            return Ok(0);
+        }
+        let tail = self.wrap(self.head + self.len);
+        let n = if tail < self.head {
+            reader.read(&mut self.bytes[tail..self.head])?
+        } else {
+            let (front, back) = self.bytes.split_at_mut(tail);
+            let mut slices = [IoSliceMut::new(back), IoSliceMut::new(&mut front[..self.head])];
+            reader.read_vectored(&mut slices)?
+        };
+        self.len += n;
+        Ok(n)
+    }
+
+    /// Index of storage for `index` which may run past the end once.
+    fn wrap(&self, index: usize) -> usize {
+        if index >= self.capacity() {
+            index - self.capacity()
+        } else {
+            index
+        }
+    }
+}
+
+impl WriteBuffer for RingBuffer {
+    /// # Panic
+    /// when `bytes` is longer than `remaining`.
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        assert!(
+            self.has_buffer(bytes.len()),
+            "no room for {} bytes; only {} bytes remain",
+            bytes.len(),
+            self.remaining()
+        );
+
+        let tail = self.wrap(self.head + self.len);
+        let n = (self.capacity() - tail).min(bytes.len());
+        self.bytes[tail..tail + n].copy_from_slice(&bytes[..n]);
+        self.bytes[..bytes.len() - n].copy_from_slice(&bytes[n..]);
+        self.len += bytes.len();
+    }
+
+    fn has_buffer(&self, size: usize) -> bool {
+        size <= self.remaining()
+    }
+
+    /// Only unread bytes are kept, and `None` is returned
+    /// when the last `len` bytes wrap around the end of storage.
+    fn written_mut(&mut self, len: usize) -> Option<&mut [u8]> {
+        if self.len < len {
+            return None;
+        }
+        let end = self.head + self.len;
+        let start = end - len;
+        if end <= self.capacity() {
+            Some(&mut self.bytes[start..end])
+        } else if start >= self.capacity() {
+            let capacity = self.capacity();
+            Some(&mut self.bytes[start - capacity..end - capacity])
+        } else {
+            None
+        }
+    }
+
+    /// The bytes must fit before the end of storage.
+    fn keeps_written(&self, len: usize) -> bool {
+        let tail = self.wrap(self.head + self.len);
+        self.has_buffer(len) && tail + len <= self.capacity()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +236,61 @@ mod tests {
         buffer.drop_front(64);
         assert_eq!(&buffer.as_ref()[0..5], &[0, 0, 1, 2, 3][..]);
     }
+
+    #[test]
+    fn ring_buffer_backpressure() {
+        let mut buffer = RingBuffer::new(8);
+        buffer.write_bytes(&[1, 2, 3, 4, 5, 6]);
+        assert!(!buffer.has_buffer(3));
+        assert!(buffer.try_write_bytes(&[7, 8, 9]).is_err());
+        assert_eq!(buffer.as_slices(), (&[1, 2, 3, 4, 5, 6][..], &[][..]));
+
+        // Wrap around the end of storage.
+        buffer.drop_front(4);
+        buffer.write_bytes(&[7, 8, 9, 10, 11, 12]);
+        assert!(buffer.is_full());
+        assert_eq!(buffer.as_slices(), (&[5, 6, 7, 8][..], &[9, 10, 11, 12][..]));
+
+        buffer.drop_front(8);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.remaining(), 8);
+    }
+
+    #[test]
+    fn ring_buffer_read_from() {
+        let mut buffer = RingBuffer::new(4);
+        let mut src = &[1, 2, 3, 4, 5, 6, 7][..];
+        assert_eq!(buffer.read_from(&mut src).unwrap(), 4);
+        assert_eq!(buffer.read_from(&mut src).unwrap(), 0);
+        assert!(buffer.is_full());
+
+        // Space before the first unread byte is used.
+        buffer.drop_front(3);
+        assert_eq!(buffer.read_from(&mut src).unwrap(), 3);
+        assert_eq!(buffer.as_slices(), (&[4][..], &[5, 6, 7][..]));
+
+        // Free space both after the last unread byte and before the first one.
+        let mut buffer = RingBuffer::new(4);
+        buffer.write_bytes(&[1, 2]);
+        buffer.drop_front(1);
+        let mut src = &[8, 9, 10][..];
+        assert_eq!(buffer.read_from(&mut src).unwrap(), 3);
+        assert_eq!(buffer.as_slices(), (&[2, 8, 9][..], &[10][..]));
+    }
+
+    #[test]
+    fn ring_buffer_never_moves_bytes() {
+        let mut buffer = RingBuffer::new(8);
+        buffer.write_bytes(&[1, 2, 3, 4, 5, 6, 7]);
+        buffer.drop_front(5);
+        buffer.write_bytes(&[8, 9, 10, 11]);
+        assert_eq!((buffer.head, buffer.len), (5, 6));
+        assert_eq!(buffer.as_slices(), (&[6, 7, 8][..], &[9, 10, 11][..]));
+
+        assert_eq!(buffer.written_mut(3).unwrap(), &mut [9, 10, 11]);
+        assert!(buffer.written_mut(4).is_none());
+
+        assert_eq!(buffer.make_contiguous(), &mut [6, 7, 8, 9, 10, 11]);
+        assert_eq!(buffer.as_slices(), (&[6, 7, 8, 9, 10, 11][..], &[][..]));
+    }
 }
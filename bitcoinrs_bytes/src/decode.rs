@@ -1,21 +1,265 @@
+use std::error::Error;
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::io::Cursor;
+
+use hex::{self, FromHexError};
+
 pub trait Decodable {
     /// Decode `Self` from given bytes.
     fn decode<R: ReadBuffer>(buf: &mut R) -> Result<Self, DecodeError>
     where
         Self: Sized;
+
+    /// Decode `Self` from hex string. Whole bytes must be consumed.
+    fn from_hex(s: &str) -> Result<Self, FromHexError>
+    where
+        Self: Sized,
+    {
+        let bytes = hex::decode(s)?;
+        let mut buf = Cursor::new(bytes.as_slice());
+        let decoded = buf.read::<Self>()?;
+        let rest = bytes.len() - buf.offset();
+        if rest != 0 {
+            return Err(FromHexError::TrailingBytes(rest));
+        }
+        Ok(decoded)
+    }
+}
+
+/// Error while decoding bytes.
+///
+/// Besides what went wrong, it records the byte offset where it went wrong
+/// and the path of fields which were being decoded, e.g. `payload.addr_recv.port`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    kind: DecodeErrorKind,
+    offset: Option<usize>,
+    /// Innermost field comes first.
+    path: Vec<&'static str>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DecodeError {
+pub enum DecodeErrorKind {
+    /// Buffer ended before whole item is read.
     ShortBuffer,
     InvalidBytes,
+    UnknownMagic(u32),
+    UnexpectedCommand([u8; 12]),
+    ChecksumMismatch { expected: [u8; 4], actual: [u8; 4] },
+    InvalidUtf8,
+    /// Length prefix exceeds the allowed maximum.
+    OversizeLength { len: u64, max: u64 },
+    /// `CompactSize` is not encoded in its shortest form.
+    NonCanonicalCompactSize,
+    /// Item is larger than `DecodeContext::max_size`.
+    SizeLimitExceeded { max: usize },
+    /// Underlying reader failed with other than `WouldBlock` or `Interrupted`.
+    Io(::std::io::ErrorKind),
+}
+
+impl DecodeError {
+    pub fn new(kind: DecodeErrorKind) -> DecodeError {
+        DecodeError {
+            kind,
+            offset: None,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn kind(&self) -> DecodeErrorKind {
+        self.kind
+    }
+
+    /// Byte offset in the buffer where this error occurred.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Path of fields from outermost to innermost.
+    pub fn path(&self) -> Vec<&'static str> {
+        self.path.iter().rev().cloned().collect()
+    }
+
+    /// Set byte offset unless it is already set by more inner decoder.
+    pub fn at(mut self, offset: usize) -> DecodeError {
+        if self.offset.is_none() {
+            self.offset = Some(offset);
+        }
+        self
+    }
+
+    /// Shift byte offset. Useful when decoding from a sub buffer which starts at `base`.
+    pub fn offset_by(mut self, base: usize) -> DecodeError {
+        self.offset = self.offset.map(|o| o + base);
+        self
+    }
+
+    /// Push a name of field which encloses this error.
+    pub fn context(mut self, field: &'static str) -> DecodeError {
+        self.path.push(field);
+        self
+    }
+}
+
+impl From<DecodeErrorKind> for DecodeError {
+    fn from(kind: DecodeErrorKind) -> DecodeError {
+        DecodeError::new(kind)
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "{}", self.kind)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at byte {}", offset)?;
+        }
+        if !self.path.is_empty() {
+            write!(f, " ({})", self.path().join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            DecodeErrorKind::ShortBuffer => write!(f, "buffer is too short"),
+            DecodeErrorKind::InvalidBytes => write!(f, "invalid bytes"),
+            DecodeErrorKind::UnknownMagic(magic) => write!(f, "unknown magic 0x{:08x}", magic),
+            DecodeErrorKind::UnexpectedCommand(command) => {
+                write!(f, "unexpected command {:?}", String::from_utf8_lossy(&command))
+            }
+            DecodeErrorKind::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch; expected {:?} but computed {:?}",
+                expected, actual
+            ),
+            DecodeErrorKind::InvalidUtf8 => write!(f, "invalid utf-8 string"),
+            DecodeErrorKind::OversizeLength { len, max } => {
+                write!(f, "length {} exceeds maximum {}", len, max)
+            }
+            DecodeErrorKind::NonCanonicalCompactSize => write!(f, "non canonical compact size"),
+            DecodeErrorKind::SizeLimitExceeded { max } => {
+                write!(f, "item exceeds size limit {}", max)
+            }
+            DecodeErrorKind::Io(kind) => write!(f, "io error: {:?}", kind),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Same as Bitcoin Core's `MAX_SIZE`.
+pub const MAX_ALLOC_SIZE: usize = 0x0200_0000;
+
+/// Same as Bitcoin Core's `MAX_VECTOR_ALLOCATE`.
+/// Vectors reserve memory in chunks of this many bytes as their items are decoded,
+/// rather than all at once for the count they claim.
+pub const MAX_VECTOR_ALLOCATE: usize = 5_000_000;
+
+/// Limits applied while decoding untrusted bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeContext {
+    /// Maximum length a length prefix may claim.
+    /// Decoders must check it before allocating memory for the item.
+    pub max_alloc: usize,
+    /// Maximum number of bytes which can be read.
+    pub max_size: usize,
+    /// Reject non canonical `CompactSize` as Bitcoin Core's `ReadCompactSize` does.
+    pub strict: bool,
+}
+
+impl DecodeContext {
+    /// Context for bytes from a peer.
+    /// It is the same as the default one except that `CompactSize` must be canonical.
+    pub fn strict() -> DecodeContext {
+        DecodeContext {
+            strict: true,
+            ..DecodeContext::default()
+        }
+    }
+
+    /// Check a length prefix against `max_alloc`.
+    pub fn check_alloc(&self, len: u64) -> Result<usize, DecodeError> {
+        if len > self.max_alloc as u64 {
+            let kind = DecodeErrorKind::OversizeLength {
+                len,
+                max: self.max_alloc as u64,
+            };
+            return Err(DecodeError::new(kind));
+        }
+        Ok(len as usize)
+    }
+}
+
+impl Default for DecodeContext {
+    fn default() -> DecodeContext {
+        DecodeContext {
+            max_alloc: MAX_ALLOC_SIZE,
+            max_size: usize::MAX,
+            strict: false,
+        }
+    }
 }
 
 pub trait ReadBuffer: Sized {
     fn read_bytes(&mut self, size: usize) -> Result<&[u8], DecodeError>;
 
+    /// Current byte offset from the beginning of this buffer.
+    fn offset(&self) -> usize;
+
+    /// Limits which decoders should obey.
+    fn context(&self) -> DecodeContext {
+        DecodeContext::default()
+    }
+
+    /// Wrap this buffer to decode with `ctx` instead of the default context.
+    fn with_context(self, ctx: DecodeContext) -> WithContext<Self> {
+        WithContext::new(self, ctx)
+    }
+
     fn read<D: Decodable>(&mut self) -> Result<D, DecodeError> {
-        D::decode(self)
+        let start = self.offset();
+        D::decode(self).map_err(|e| e.at(start))
+    }
+
+    /// Same as `read` but also record `field` in the path of error.
+    fn read_field<D: Decodable>(&mut self, field: &'static str) -> Result<D, DecodeError> {
+        self.read::<D>().map_err(|e| e.context(field))
+    }
+}
+
+/// Decodable from bytes which borrows from the input buffer instead of copying them.
+///
+/// Every `Decodable` type is also `DecodableBorrowed`, so a borrowing decoder can
+/// freely mix owned and borrowed fields.
+pub trait DecodableBorrowed<'a>: Sized {
+    /// Decode `Self` from given bytes, borrowing from them.
+    fn decode_borrowed<R: BorrowedReadBuffer<'a>>(buf: &mut R) -> Result<Self, DecodeError>;
+}
+
+impl<'a, D: Decodable> DecodableBorrowed<'a> for D {
+    fn decode_borrowed<R: BorrowedReadBuffer<'a>>(buf: &mut R) -> Result<D, DecodeError> {
+        D::decode(buf)
+    }
+}
+
+/// `ReadBuffer` whose underlying bytes outlive the buffer itself.
+pub trait BorrowedReadBuffer<'a>: ReadBuffer {
+    /// Same as `ReadBuffer::read_bytes` but returned bytes live as long as `'a`.
+    fn read_borrowed_bytes(&mut self, size: usize) -> Result<&'a [u8], DecodeError>;
+
+    fn read_borrowed<D: DecodableBorrowed<'a>>(&mut self) -> Result<D, DecodeError> {
+        let start = self.offset();
+        D::decode_borrowed(self).map_err(|e| e.at(start))
+    }
+
+    /// Same as `read_borrowed` but also record `field` in the path of error.
+    fn read_borrowed_field<D>(&mut self, field: &'static str) -> Result<D, DecodeError>
+    where
+        D: DecodableBorrowed<'a>,
+    {
+        self.read_borrowed::<D>().map_err(|e| e.context(field))
     }
 }
 
@@ -24,32 +268,119 @@ where
     B: AsRef<[u8]>,
 {
     fn read_bytes(&mut self, size: usize) -> Result<&[u8], DecodeError> {
-        let start_pos = self.position() as usize; // Should I check here?
+        self.read_borrowed_bytes(size)
+    }
 
-        if self.get_ref().as_ref().len() < start_pos + size {
-            return Err(DecodeError::ShortBuffer);
-        }
-        self.set_position((start_pos + size) as u64);
+    fn offset(&self) -> usize {
+        self.position() as usize
+    }
+}
 
-        let buf = &self.get_ref().as_ref()[start_pos..];
+impl<'a, B> BorrowedReadBuffer<'a> for ::std::io::Cursor<&'a B>
+where
+    B: AsRef<[u8]>,
+{
+    fn read_borrowed_bytes(&mut self, size: usize) -> Result<&'a [u8], DecodeError> {
+        let bytes = *self.get_ref();
+        let (read, pos) = read_slice(bytes.as_ref(), self.position() as usize, size)?;
+        self.set_position(pos as u64);
+        Ok(read)
+    }
+}
 
-        Ok(&buf[..size])
+impl<'a> ReadBuffer for ::std::io::Cursor<&'a [u8]> {
+    fn read_bytes(&mut self, size: usize) -> Result<&[u8], DecodeError> {
+        self.read_borrowed_bytes(size)
+    }
+
+    fn offset(&self) -> usize {
+        self.position() as usize
     }
 }
 
-impl<'a> ReadBuffer for ::std::io::Cursor<&'a [u8]>
-{
+impl<'a> BorrowedReadBuffer<'a> for ::std::io::Cursor<&'a [u8]> {
+    fn read_borrowed_bytes(&mut self, size: usize) -> Result<&'a [u8], DecodeError> {
+        let bytes = *self.get_ref();
+        let (read, pos) = read_slice(bytes, self.position() as usize, size)?;
+        self.set_position(pos as u64);
+        Ok(read)
+    }
+}
+
+impl ReadBuffer for ::std::io::Cursor<Vec<u8>> {
     fn read_bytes(&mut self, size: usize) -> Result<&[u8], DecodeError> {
-        let start_pos = self.position() as usize; // Should I check here?
+        let start_pos = self.position() as usize;
+        let (_, pos) = read_slice(self.get_ref(), start_pos, size)?;
+        self.set_position(pos as u64);
+        Ok(&self.get_ref()[start_pos..pos])
+    }
+
+    fn offset(&self) -> usize {
+        self.position() as usize
+    }
+}
+
+/// Take `size` bytes at `start_pos` and return them with the next position.
+fn read_slice(bytes: &[u8], start_pos: usize, size: usize) -> Result<(&[u8], usize), DecodeError> {
+    if bytes.len() < start_pos || bytes.len() - start_pos < size {
+        return Err(DecodeError::new(DecodeErrorKind::ShortBuffer).at(start_pos));
+    }
+    Ok((&bytes[start_pos..start_pos + size], start_pos + size))
+}
+
+/// `ReadBuffer` adapter which decodes with given `DecodeContext`.
+/// It also fails reading more than `DecodeContext::max_size` bytes in total.
+pub struct WithContext<R> {
+    inner: R,
+    ctx: DecodeContext,
+    start: usize,
+}
+
+impl<R: ReadBuffer> WithContext<R> {
+    pub fn new(inner: R, ctx: DecodeContext) -> WithContext<R> {
+        let start = inner.offset();
+        WithContext { inner, ctx, start }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
 
-        if self.get_ref().len() < start_pos + size {
-            return Err(DecodeError::ShortBuffer);
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn check_size(&self, size: usize) -> Result<(), DecodeError> {
+        let read = self.inner.offset() - self.start;
+        if self.ctx.max_size - read < size {
+            let kind = DecodeErrorKind::SizeLimitExceeded {
+                max: self.ctx.max_size,
+            };
+            return Err(DecodeError::new(kind).at(self.inner.offset()));
         }
-        self.set_position((start_pos + size) as u64);
+        Ok(())
+    }
+}
+
+impl<R: ReadBuffer> ReadBuffer for WithContext<R> {
+    fn read_bytes(&mut self, size: usize) -> Result<&[u8], DecodeError> {
+        self.check_size(size)?;
+        self.inner.read_bytes(size)
+    }
 
-        let buf = &self.get_ref()[start_pos..];
+    fn offset(&self) -> usize {
+        self.inner.offset()
+    }
+
+    fn context(&self) -> DecodeContext {
+        self.ctx
+    }
+}
 
-        Ok(&buf[..size])
+impl<'a, R: BorrowedReadBuffer<'a>> BorrowedReadBuffer<'a> for WithContext<R> {
+    fn read_borrowed_bytes(&mut self, size: usize) -> Result<&'a [u8], DecodeError> {
+        self.check_size(size)?;
+        self.inner.read_borrowed_bytes(size)
     }
 }
 
@@ -79,10 +410,23 @@ macro_rules! impl_decodable_for_array {
     ( $size: expr ) => {
         impl Decodable for [u8; $size] {
             fn decode<R: ReadBuffer>(buf: &mut R) -> Result<Self, DecodeError> {
-                let array = unsafe{ *(buf.read_bytes($size)? as *const _ as *const [u8; $size]) };
+                let mut array = [0; $size];
+                array.copy_from_slice(buf.read_bytes($size)?);
                 Ok(array)
             }
         }
+
+        impl<'a> DecodableBorrowed<'a> for &'a [u8; $size] {
+            fn decode_borrowed<R>(buf: &mut R) -> Result<Self, DecodeError>
+            where
+                R: BorrowedReadBuffer<'a>,
+            {
+                let bytes = buf.read_borrowed_bytes($size)?;
+                // Never fails because exactly `$size` bytes are read.
+                <&[u8; $size] as ::std::convert::TryFrom<&[u8]>>::try_from(bytes)
+                    .map_err(|_| DecodeError::new(DecodeErrorKind::ShortBuffer))
+            }
+        }
     };
     ( $size: expr, $( $sizes: expr ),* ) => {
         impl_decodable_for_array!($size);
@@ -91,4 +435,4 @@ macro_rules! impl_decodable_for_array {
 }
 
 impl_decodable_for_array!(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
-impl_decodable_for_array!(32, 64, 128, 256, 512, 1024, 2048, 4096);
+impl_decodable_for_array!(20, 32, 64, 128, 256, 512, 1024, 2048, 4096);
@@ -2,6 +2,11 @@
 #![allow(dead_code)]
 
 use std::borrow::Borrow;
+use std::error::Error;
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::io::ErrorKind;
+
+use hex;
 
 /// Encodable into an array of byte.
 pub trait Encodable {
@@ -15,8 +20,26 @@ pub trait Encodable {
     ///
     /// # Panic
     /// when underlying buffer has not enough buffer to write.
+    /// Use `Encodable::try_encode` to get an error instead.
     fn encode<W: WriteBuffer>(&self, buf: &mut W);
 
+    /// Same as `encode` but return an error instead of panic
+    /// when underlying buffer has not enough buffer to write.
+    /// Nothing is written on `EncodeError::ShortBuffer`. On `EncodeError::Io` the
+    /// all-or-nothing guarantee does not hold; an I/O stream may have accepted
+    /// part of the bytes before it failed, and they can not be taken back.
+    fn try_encode<W: WriteBuffer>(&self, buf: &mut W) -> Result<(), EncodeError> {
+        if let Some(e) = buf.error() {
+            return Err(e);
+        }
+        let required = self.length();
+        if !buf.has_buffer(required) {
+            return Err(EncodeError::ShortBuffer { required });
+        }
+        self.encode(buf);
+        buf.error().map_or(Ok(()), Err)
+    }
+
     /// Chain two `Encodable` struct into single.
     /// This operation is atomic.
     fn chain<'a, 'b, E>(&'a self, e2: &'b E) -> Chain<'a, 'b, Self, E>
@@ -33,6 +56,11 @@ pub trait Encodable {
         self.encode(&mut vec);
         vec
     }
+
+    /// Convenient function to create lower case hex string of encoded bytes.
+    fn to_hex(&self) -> String {
+        hex::encode(&self.to_vec())
+    }
 }
 
 impl<'a> Encodable for &'a [u8] {
@@ -51,16 +79,80 @@ pub trait WriteBuffer: Sized {
     ///
     /// # Panic
     /// when underlying buffer has not enough buffer to write.
+    /// Use `WriteBuffer::try_write_bytes` to get an error instead.
     fn write_bytes(&mut self, bytes: &[u8]);
 
+    /// Same as `write_bytes` but return an error instead of panic.
+    /// Nothing is written on `EncodeError::ShortBuffer`. On `EncodeError::Io`
+    /// part of the bytes may have reached an I/O stream already.
+    fn try_write_bytes(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        if let Some(e) = self.error() {
+            return Err(e);
+        }
+        if !self.has_buffer(bytes.len()) {
+            return Err(EncodeError::ShortBuffer {
+                required: bytes.len(),
+            });
+        }
+        self.write_bytes(bytes);
+        self.error().map_or(Ok(()), Err)
+    }
+
     /// Check whether this buffer has enough buffer.
     fn has_buffer(&self, size: usize) -> bool;
 
+    /// Mutable reference to the last `len` bytes written, if this buffer keeps them.
+    /// It enables an `Encodable` to fill a header field such as a checksum
+    /// after the following bytes are written.
+    /// Check `keeps_written` before writing them.
+    fn written_mut(&mut self, _len: usize) -> Option<&mut [u8]> {
+        None
+    }
+
+    /// Whether `written_mut(len)` returns `Some` once `len` more bytes are written.
+    /// Implementations must keep this promise as long as nothing but writes happens
+    /// to the buffer in between.
+    fn keeps_written(&self, _len: usize) -> bool {
+        false
+    }
+
+    /// Error which made previous writes fail, if any.
+    /// Buffers over an I/O stream can not report an error from `write_bytes`,
+    /// so they keep it here instead.
+    fn error(&self) -> Option<EncodeError> {
+        None
+    }
+
     fn write<E: Encodable>(&mut self, e: E) {
         e.encode(self)
     }
+
+    fn try_write<E: Encodable>(&mut self, e: E) -> Result<(), EncodeError> {
+        e.try_encode(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// Underlying buffer has no room for `required` bytes.
+    ShortBuffer { required: usize },
+    /// Underlying I/O stream failed.
+    Io(ErrorKind),
 }
 
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            EncodeError::ShortBuffer { required } => {
+                write!(f, "buffer has no room for {} bytes", required)
+            }
+            EncodeError::Io(kind) => write!(f, "I/O error: {:?}", kind),
+        }
+    }
+}
+
+impl Error for EncodeError {}
+
 impl WriteBuffer for Vec<u8> {
     fn write_bytes(&mut self, bytes: &[u8]) {
         self.extend_from_slice(bytes);
@@ -69,13 +161,59 @@ impl WriteBuffer for Vec<u8> {
     fn has_buffer(&self, _size: usize) -> bool {
         true
     }
+
+    fn written_mut(&mut self, len: usize) -> Option<&mut [u8]> {
+        let start = self.len().checked_sub(len)?;
+        Some(&mut self[start..])
+    }
+
+    fn keeps_written(&self, _len: usize) -> bool {
+        true
+    }
+}
+
+/// Overwrite bytes at current position and extend `Vec` if needed, like `std::io::Write`.
+impl WriteBuffer for ::std::io::Cursor<Vec<u8>> {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        let start_pos = self.position() as usize;
+        let end_pos = start_pos + bytes.len();
+        {
+            let vec = self.get_mut();
+            if vec.len() < end_pos {
+                vec.resize(end_pos, 0);
+            }
+            vec[start_pos..end_pos].copy_from_slice(bytes);
+        }
+        self.set_position(end_pos as u64);
+    }
+
+    fn has_buffer(&self, _size: usize) -> bool {
+        true
+    }
+
+    fn written_mut(&mut self, len: usize) -> Option<&mut [u8]> {
+        let end = self.position() as usize;
+        let start = end.checked_sub(len)?;
+        self.get_mut().get_mut(start..end)
+    }
+
+    fn keeps_written(&self, _len: usize) -> bool {
+        true
+    }
 }
 
 impl<'a> WriteBuffer for ::std::io::Cursor<&'a mut [u8]> {
     fn write_bytes(&mut self, bytes: &[u8]) {
         let bytes_len = bytes.len();
-        let start_pos = self.position() as usize; // Should I check here?
+        let start_pos = self.position() as usize;
 
+        assert!(
+            self.has_buffer(bytes_len),
+            "no room for {} bytes at position {} of {} bytes buffer",
+            bytes_len,
+            start_pos,
+            self.get_ref().len()
+        );
         self.set_position((start_pos + bytes_len) as u64);
 
         let buf = &mut self.get_mut()[start_pos..];
@@ -91,6 +229,16 @@ impl<'a> WriteBuffer for ::std::io::Cursor<&'a mut [u8]> {
             false
         }
     }
+
+    fn written_mut(&mut self, len: usize) -> Option<&mut [u8]> {
+        let end = self.position() as usize;
+        let start = end.checked_sub(len)?;
+        Some(&mut self.get_mut()[start..end])
+    }
+
+    fn keeps_written(&self, len: usize) -> bool {
+        self.has_buffer(len)
+    }
 }
 
 /// Encodable into a sized array of bytes.
@@ -149,3 +297,47 @@ where
         self.e2.encode(buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decode::ReadBuffer;
+    use endian::{u16_l, u32_l};
+    use std::io::Cursor;
+
+    #[test]
+    fn try_encode_into_short_buffer() {
+        let mut bytes = [0; 6];
+        let mut buf = Cursor::new(&mut bytes[..]);
+
+        let (a, b) = (u32_l::new(0x01020304), u32_l::new(0x05060708));
+        assert_eq!(
+            a.chain(&b).try_encode(&mut buf),
+            Err(EncodeError::ShortBuffer { required: 8 })
+        );
+        assert_eq!(buf.position(), 0);
+
+        assert_eq!(buf.try_write(u32_l::new(0x01020304)), Ok(()));
+        assert_eq!(
+            buf.try_write_bytes(&[9, 9, 9]),
+            Err(EncodeError::ShortBuffer { required: 3 })
+        );
+        assert_eq!(buf.position(), 4);
+        assert_eq!(bytes, [4, 3, 2, 1, 0, 0]);
+    }
+
+    #[test]
+    fn vec_cursor() {
+        let mut cursor = Cursor::new(vec![0xAA; 2]);
+        cursor.set_position(1);
+        cursor.write(u16_l::new(0x0201));
+        cursor.write(u32_l::new(0x06050403));
+        assert_eq!(cursor.get_ref(), &vec![0xAA, 1, 2, 3, 4, 5, 6]);
+
+        cursor.set_position(1);
+        assert_eq!(cursor.read::<u16_l>().unwrap().value(), 0x0201);
+        assert_eq!(cursor.read::<u32_l>().unwrap().value(), 0x06050403);
+        let e = cursor.read::<u8>().unwrap_err();
+        assert_eq!(e.offset(), Some(7));
+    }
+}
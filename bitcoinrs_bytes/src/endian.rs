@@ -14,6 +14,7 @@ pub struct i32_b(i32);
 pub struct i64_b(i64);
 
 use std::fmt::{Debug, Display, Error as FmtError, Formatter};
+use std::net::Ipv6Addr;
 
 use decode::{Decodable, DecodeError, ReadBuffer};
 use encode::EncodableSized;
@@ -32,19 +33,33 @@ macro_rules! impl_prim_endian {
             }
         }
 
+        impl From<$inner_t> for $t {
+            fn from(n: $inner_t) -> $t {
+                <$t>::new(n)
+            }
+        }
+
+        impl From<$t> for $inner_t {
+            fn from(n: $t) -> $inner_t {
+                n.value()
+            }
+        }
+
         impl EncodableSized for $t {
             const SIZE: usize = $size;
 
             type Array = [u8; $size];
 
             fn bytes(&self) -> [u8; $size] {
-                unsafe { ::std::mem::transmute::<$inner_t, [u8; $size]>(self.0) }
+                self.0.to_ne_bytes()
             }
         }
 
         impl Decodable for $t {
             fn decode<R: ReadBuffer>(buf: &mut R) -> Result<$t, DecodeError> {
-                Ok($t_exp(unsafe { *(buf.read_bytes($size)? as *const _ as *const $inner_t) }))
+                let mut bytes = [0; $size];
+                bytes.copy_from_slice(buf.read_bytes($size)?);
+                Ok($t_exp(<$inner_t>::from_ne_bytes(bytes)))
             }
         }
 
@@ -106,3 +121,35 @@ impl Decodable for i8 {
         Ok(buf.read::<u8>()? as i8)
     }
 }
+
+impl EncodableSized for bool {
+    const SIZE: usize = 1;
+    type Array = [u8; 1];
+
+    fn bytes(&self) -> [u8; 1] {
+        [*self as u8]
+    }
+}
+
+impl Decodable for bool {
+    /// Any non-zero byte is decoded as `true`.
+    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<bool, DecodeError> {
+        Ok(buf.read::<u8>()? != 0)
+    }
+}
+
+/// Ipv6 address is encoded as 16 bytes in network byte order.
+impl EncodableSized for Ipv6Addr {
+    const SIZE: usize = 16;
+    type Array = [u8; 16];
+
+    fn bytes(&self) -> [u8; 16] {
+        self.octets()
+    }
+}
+
+impl Decodable for Ipv6Addr {
+    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<Ipv6Addr, DecodeError> {
+        Ok(Ipv6Addr::from(buf.read::<[u8; 16]>()?))
+    }
+}
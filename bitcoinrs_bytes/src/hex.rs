@@ -0,0 +1,222 @@
+use std::error::Error;
+use std::fmt::{Debug, Display, Error as FmtError, Formatter};
+
+use decode::DecodeError;
+
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+/// Lower case hex string of `bytes`.
+pub fn encode(bytes: &[u8]) -> String {
+    Hex(bytes).to_string()
+}
+
+/// Lower case hex string of `bytes` in reversed order.
+/// Bitcoin Core shows txids and block hashes in this way.
+pub fn encode_reversed(bytes: &[u8]) -> String {
+    ReversedHex(bytes).to_string()
+}
+
+/// Decode hex string. Both of upper and lower case are accepted.
+pub fn decode(s: &str) -> Result<Vec<u8>, HexError> {
+    if !s.len().is_multiple_of(2) {
+        return Err(HexError::OddLength(s.len()));
+    }
+    let mut bytes = vec![0; s.len() / 2];
+    decode_to_slice(s, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Decode hex string which is shown in reversed order.
+pub fn decode_reversed(s: &str) -> Result<Vec<u8>, HexError> {
+    let mut bytes = decode(s)?;
+    bytes.reverse();
+    Ok(bytes)
+}
+
+/// Decode hex string into `out`. Length of decoded bytes must be the same as `out`.
+pub fn decode_to_slice(s: &str, out: &mut [u8]) -> Result<(), HexError> {
+    if s.len() != out.len() * 2 {
+        return Err(HexError::InvalidLength {
+            expected: out.len() * 2,
+            actual: s.len(),
+        });
+    }
+
+    for (i, byte) in out.iter_mut().enumerate() {
+        let hi = hex_value(s, i * 2)?;
+        let lo = hex_value(s, i * 2 + 1)?;
+        *byte = (hi << 4) | lo;
+    }
+    Ok(())
+}
+
+fn hex_value(s: &str, index: usize) -> Result<u8, HexError> {
+    match s.as_bytes()[index] {
+        c @ b'0'..=b'9' => Ok(c - b'0'),
+        c @ b'a'..=b'f' => Ok(c - b'a' + 10),
+        c @ b'A'..=b'F' => Ok(c - b'A' + 10),
+        // Bytes before `index` are ascii, so `index` is on a char boundary.
+        _ => Err(HexError::InvalidChar {
+            c: s[index..].chars().next().unwrap(),
+            index,
+        }),
+    }
+}
+
+/// Display bytes as lower case hex string.
+pub struct Hex<'a>(pub &'a [u8]);
+
+/// Display bytes as lower case hex string in reversed order, as Bitcoin Core shows hashes.
+pub struct ReversedHex<'a>(pub &'a [u8]);
+
+fn write_hex<'a, I>(f: &mut Formatter, bytes: I) -> Result<(), FmtError>
+where
+    I: Iterator<Item = &'a u8>,
+{
+    for byte in bytes {
+        let chars = [HEX_CHARS[(byte >> 4) as usize], HEX_CHARS[(byte & 0x0f) as usize]];
+        // Never fails because chars are ascii.
+        f.write_str(::std::str::from_utf8(&chars).unwrap())?;
+    }
+    Ok(())
+}
+
+impl<'a> Display for Hex<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write_hex(f, self.0.iter())
+    }
+}
+
+impl<'a> Debug for Hex<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write_hex(f, self.0.iter())
+    }
+}
+
+impl<'a> Display for ReversedHex<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write_hex(f, self.0.iter().rev())
+    }
+}
+
+impl<'a> Debug for ReversedHex<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write_hex(f, self.0.iter().rev())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    /// `c` at byte offset `index` is not a hex digit.
+    InvalidChar { c: char, index: usize },
+    /// Hex string must have even length.
+    OddLength(usize),
+    /// Hex string does not have the expected length.
+    InvalidLength { expected: usize, actual: usize },
+}
+
+impl Display for HexError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            HexError::InvalidChar { c, index } => {
+                write!(f, "invalid hex character {:?} at {}", c, index)
+            }
+            HexError::OddLength(len) => write!(f, "odd length {} of hex string", len),
+            HexError::InvalidLength { expected, actual } => write!(
+                f,
+                "hex string has length {} but {} is expected",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl Error for HexError {}
+
+/// Error from `Decodable::from_hex`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromHexError {
+    Hex(HexError),
+    Decode(DecodeError),
+    /// Decoded item does not consume whole bytes.
+    TrailingBytes(usize),
+}
+
+impl From<HexError> for FromHexError {
+    fn from(e: HexError) -> FromHexError {
+        FromHexError::Hex(e)
+    }
+}
+
+impl From<DecodeError> for FromHexError {
+    fn from(e: DecodeError) -> FromHexError {
+        FromHexError::Decode(e)
+    }
+}
+
+impl Display for FromHexError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            FromHexError::Hex(ref e) => write!(f, "{}", e),
+            FromHexError::Decode(ref e) => write!(f, "{}", e),
+            FromHexError::TrailingBytes(n) => write!(f, "{} trailing bytes", n),
+        }
+    }
+}
+
+impl Error for FromHexError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decode::Decodable;
+    use encode::Encodable;
+    use endian::u32_l;
+
+    #[test]
+    fn encode_decode() {
+        let bytes = [0x00, 0x01, 0xab, 0xff];
+        assert_eq!(encode(&bytes), "0001abff");
+        assert_eq!(encode_reversed(&bytes), "ffab0100");
+        assert_eq!(decode("0001ABff").unwrap(), bytes.to_vec());
+        assert_eq!(decode_reversed("ffab0100").unwrap(), bytes.to_vec());
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert_eq!(decode("abc"), Err(HexError::OddLength(3)));
+        assert_eq!(
+            decode("0g"),
+            Err(HexError::InvalidChar { c: 'g', index: 1 })
+        );
+        assert_eq!(
+            decode("é"),
+            Err(HexError::InvalidChar { c: 'é', index: 0 })
+        );
+        assert_eq!(
+            decode("00€0"),
+            Err(HexError::InvalidChar { c: '€', index: 2 })
+        );
+        assert_eq!(
+            decode("é").unwrap_err().to_string(),
+            "invalid hex character 'é' at 0"
+        );
+        assert_eq!(
+            decode_to_slice("00", &mut [0; 2]),
+            Err(HexError::InvalidLength {
+                expected: 4,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn encodable_to_hex() {
+        assert_eq!(u32_l::new(1).to_hex(), "01000000");
+        assert_eq!(u32_l::from_hex("01000000").unwrap().value(), 1);
+        assert_eq!(
+            u32_l::from_hex("0100000000").unwrap_err(),
+            FromHexError::TrailingBytes(1)
+        );
+    }
+}
@@ -1,4 +1,14 @@
+#[macro_use]
+mod macros;
+
 pub mod encode;
 pub mod decode;
 pub mod endian;
 pub mod buffer;
+pub mod stream;
+pub mod hex;
+pub mod base58;
+pub mod bech32;
+pub mod var;
+pub mod bits;
+pub mod uint;
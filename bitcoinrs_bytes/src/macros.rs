@@ -0,0 +1,78 @@
+/// Implement `Encodable` and `Decodable` for a struct from a single list of its fields.
+///
+/// Fields are encoded and decoded in the listed order.
+/// When decoding fails, the name of the field is pushed onto the path of `DecodeError`.
+/// A field whose own type is already `Encodable` and `Decodable` is written as its name.
+/// A field which is stored as one type but written as another is written as
+/// `name as WireType`; `WireType` must be convertible from and into the field type
+/// by `From`, and the field type must be `Copy`. Only one `as WireType` is allowed per field.
+///
+/// # Example
+/// ```
+/// #[macro_use]
+/// extern crate bitcoinrs_bytes;
+///
+/// use bitcoinrs_bytes::endian::{u16_b, u32_l};
+///
+/// struct Entry {
+///     flag: u8,
+///     height: u32,
+///     port: u16,
+/// }
+///
+/// impl_codec!(Entry {
+///     flag,
+///     height as u32_l,
+///     port as u16_b,
+/// });
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! impl_codec {
+    (@length $self_: ident, $field: ident) => {
+        (&$self_.$field).length()
+    };
+    (@length $self_: ident, $field: ident, $wire: ty) => {
+        <$wire>::from($self_.$field).length()
+    };
+
+    (@encode $self_: ident, $buf: ident, $field: ident) => {
+        (&$self_.$field).encode($buf)
+    };
+    (@encode $self_: ident, $buf: ident, $field: ident, $wire: ty) => {
+        <$wire>::from($self_.$field).encode($buf)
+    };
+
+    (@decode $buf: ident, $field: ident) => {
+        $crate::decode::ReadBuffer::read_field($buf, stringify!($field))?
+    };
+    (@decode $buf: ident, $field: ident, $wire: ty) => {
+        From::from($crate::decode::ReadBuffer::read_field::<$wire>($buf, stringify!($field))?)
+    };
+
+    ($t: ident { $( $field: ident $( as $wire: ty )? ),* $(,)? }) => {
+        impl $crate::encode::Encodable for $t {
+            fn length(&self) -> usize {
+                #[allow(unused_imports)]
+                use $crate::encode::Encodable;
+                0 $( + impl_codec!(@length self, $field $(, $wire)?) )*
+            }
+
+            fn encode<W: $crate::encode::WriteBuffer>(&self, buf: &mut W) {
+                #[allow(unused_imports)]
+                use $crate::encode::Encodable;
+                $( impl_codec!(@encode self, buf, $field $(, $wire)?); )*
+            }
+        }
+
+        impl $crate::decode::Decodable for $t {
+            fn decode<R: $crate::decode::ReadBuffer>(
+                buf: &mut R,
+            ) -> Result<$t, $crate::decode::DecodeError> {
+                Ok($t {
+                    $( $field: impl_codec!(@decode buf, $field $(, $wire)?), )*
+                })
+            }
+        }
+    };
+}
@@ -0,0 +1,303 @@
+use std::io::{Error as IoError, ErrorKind, Read, Write};
+
+use decode::{Decodable, DecodeError, DecodeErrorKind, ReadBuffer};
+use encode::{EncodeError, WriteBuffer};
+
+/// Bytes requested from underlying reader at once.
+const READ_CHUNK_SIZE: usize = 4096;
+
+/// `ReadBuffer` adapter over any `std::io::Read`.
+///
+/// Bytes are pulled from the underlying reader only when a decoder asks for
+/// more than is buffered, so a message does not need to be buffered up front.
+///
+/// `StreamReader::decode` is resumable. When the reader reaches EOF or returns
+/// `WouldBlock` in the middle of an item, `DecodeErrorKind::ShortBuffer` is returned
+/// and every byte read so far is kept. Calling `decode` again once more bytes
+/// are available restarts the item from its first byte.
+pub struct StreamReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    /// Bytes already discarded from `buf`.
+    consumed: usize,
+}
+
+impl<R: Read> StreamReader<R> {
+    pub fn new(inner: R) -> StreamReader<R> {
+        StreamReader {
+            inner,
+            buf: Vec::new(),
+            pos: 0,
+            consumed: 0,
+        }
+    }
+
+    /// Decode a `D` from the stream.
+    /// On success, consumed bytes are discarded.
+    /// On failure, nothing is consumed and the same item can be decoded again.
+    pub fn decode<D: Decodable>(&mut self) -> Result<D, DecodeError> {
+        let start_pos = self.pos;
+        match D::decode(self) {
+            Ok(d) => {
+                self.consume();
+                Ok(d)
+            }
+            Err(e) => {
+                self.pos = start_pos;
+                Err(e)
+            }
+        }
+    }
+
+    /// Bytes which are already read from underlying reader but not consumed yet.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Note that buffered bytes are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Discard bytes before current position.
+    fn consume(&mut self) {
+        self.buf.drain(..self.pos);
+        self.consumed += self.pos;
+        self.pos = 0;
+    }
+
+    /// Read from underlying reader until at least `size` bytes are buffered after current position.
+    /// Buffer grows by `READ_CHUNK_SIZE` at most per read, so a large `size` taken from
+    /// a length prefix does not allocate memory before the bytes actually arrive.
+    fn fill(&mut self, size: usize) -> Result<(), DecodeError> {
+        while self.buf.len() - self.pos < size {
+            let filled = self.buf.len();
+            self.buf.resize(filled + READ_CHUNK_SIZE, 0);
+
+            match self.inner.read(&mut self.buf[filled..]) {
+                Ok(0) => {
+                    // Reached EOF.
+                    self.buf.truncate(filled);
+                    return Err(self.short_buffer());
+                }
+                Ok(n) => self.buf.truncate(filled + n),
+                Err(e) => {
+                    self.buf.truncate(filled);
+                    match e.kind() {
+                        ErrorKind::Interrupted => {}
+                        ErrorKind::WouldBlock => return Err(self.short_buffer()),
+                        kind => {
+                            let e = DecodeError::new(DecodeErrorKind::Io(kind));
+                            return Err(e.at(self.offset()));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn short_buffer(&self) -> DecodeError {
+        DecodeError::new(DecodeErrorKind::ShortBuffer).at(self.offset())
+    }
+}
+
+impl<R: Read> ReadBuffer for StreamReader<R> {
+    fn read_bytes(&mut self, size: usize) -> Result<&[u8], DecodeError> {
+        self.fill(size)?;
+
+        let start_pos = self.pos;
+        self.pos += size;
+
+        Ok(&self.buf[start_pos..self.pos])
+    }
+
+    /// Offset from the beginning of the stream.
+    fn offset(&self) -> usize {
+        self.consumed + self.pos
+    }
+}
+
+/// `WriteBuffer` adapter over any `std::io::Write`.
+///
+/// Bytes are passed to the underlying writer as soon as they are written,
+/// so wrap it with `std::io::BufWriter` when it is costly to write small chunks.
+///
+/// `WriteBuffer::write_bytes` can not return an error. So the first I/O error is kept,
+/// following writes are ignored, and the error is reported by
+/// `Encodable::try_encode`, `WriteBuffer::try_write_bytes` and `StreamWriter::finish`.
+pub struct StreamWriter<W> {
+    inner: W,
+    error: Option<IoError>,
+    /// Bytes of completed writes.
+    written: usize,
+}
+
+impl<W: Write> StreamWriter<W> {
+    pub fn new(inner: W) -> StreamWriter<W> {
+        StreamWriter {
+            inner,
+            error: None,
+            written: 0,
+        }
+    }
+
+    /// Total length of `write_bytes` calls which completed.
+    /// Bytes of the write which failed are not counted,
+    /// even when the underlying writer accepted part of them.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// The first I/O error which made writes fail.
+    pub fn io_error(&self) -> Option<&IoError> {
+        self.error.as_ref()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Flush underlying writer and return it, or the first I/O error.
+    pub fn finish(mut self) -> Result<W, IoError> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> WriteBuffer for StreamWriter<W> {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.error.is_some() {
+            return;
+        }
+        match self.inner.write_all(bytes) {
+            Ok(()) => self.written += bytes.len(),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn has_buffer(&self, _size: usize) -> bool {
+        true
+    }
+
+    fn error(&self) -> Option<EncodeError> {
+        self.error.as_ref().map(|e| EncodeError::Io(e.kind()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encode::Encodable;
+    use endian::{u16_l, u32_l};
+
+    /// Yield at most one byte per `read` call, and `WouldBlock` every other call.
+    struct Trickle {
+        bytes: Vec<u8>,
+        pos: usize,
+        block: bool,
+    }
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+            self.block = !self.block;
+            if self.block {
+                return Err(IoError::from(ErrorKind::WouldBlock));
+            }
+            if self.pos == self.bytes.len() {
+                return Ok(0);
+            }
+            buf[0] = self.bytes[self.pos];
+            self.pos += 1;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn resume_decoding() {
+        let trickle = Trickle {
+            bytes: vec![1, 0, 0, 0, 2, 0, 3, 0],
+            pos: 0,
+            block: false,
+        };
+        let mut reader = StreamReader::new(trickle);
+
+        let mut attempts = 0;
+        let (a, b) = loop {
+            attempts += 1;
+            match reader.decode::<(u32_l, u16_l)>() {
+                Ok(item) => break item,
+                Err(ref e) if e.kind() == DecodeErrorKind::ShortBuffer => continue,
+                Err(e) => panic!("{}", e),
+            }
+        };
+        assert_eq!((a.value(), b.value()), (1, 2));
+        assert!(attempts > 1);
+        assert!(reader.buffered().is_empty());
+
+        loop {
+            match reader.decode::<u16_l>() {
+                Ok(c) => break assert_eq!(c.value(), 3),
+                Err(ref e) if e.kind() == DecodeErrorKind::ShortBuffer => {
+                    assert_eq!(e.offset(), Some(6));
+                    continue;
+                }
+                Err(e) => panic!("{}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn large_item() {
+        let bytes = vec![7; READ_CHUNK_SIZE * 3 + 1];
+        let mut reader = StreamReader::new(&bytes[..]);
+        assert_eq!(reader.read_bytes(bytes.len()).unwrap(), &bytes[..]);
+        let e = reader.decode::<u8>().unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::ShortBuffer);
+        assert_eq!(e.offset(), Some(bytes.len()));
+    }
+
+    #[test]
+    fn large_size_with_few_bytes() {
+        let bytes = [1, 2, 3];
+        let mut reader = StreamReader::new(&bytes[..]);
+        let e = reader.read_bytes(32 * 1024 * 1024).unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::ShortBuffer);
+        assert!(reader.buf.capacity() <= READ_CHUNK_SIZE * 2);
+        assert_eq!(reader.buffered(), &bytes[..]);
+    }
+
+    #[test]
+    fn stream_writer_reports_io_error() {
+        let mut bytes = [0; 6];
+        {
+            let mut writer = StreamWriter::new(&mut bytes[..]);
+            assert_eq!(u32_l::new(0x01020304).try_encode(&mut writer), Ok(()));
+            assert_eq!(
+                u32_l::new(0x05060708).try_encode(&mut writer),
+                Err(EncodeError::Io(ErrorKind::WriteZero))
+            );
+            // Following writes are ignored.
+            writer.write(0xFFu8);
+            assert_eq!(writer.written(), 4);
+            assert_eq!(writer.finish().unwrap_err().kind(), ErrorKind::WriteZero);
+        }
+        assert_eq!(&bytes[..4], &[4, 3, 2, 1]);
+    }
+}
@@ -0,0 +1,509 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{Debug, Display, Error as FmtError, Formatter, LowerHex};
+use std::ops::{Add, Div, Mul, Not, Rem, Shl, Shr, Sub};
+
+use decode::{Decodable, DecodeError, ReadBuffer};
+use encode::EncodableSized;
+
+/// 256-bit unsigned integer for proof-of-work targets and chainwork.
+///
+/// Operators panic on overflow and division by zero.
+/// Use `checked_*` or `overflowing_*` functions to handle them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct U256([u64; 4]); // Least significant limb first.
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub fn from_u64(n: u64) -> U256 {
+        U256([n, 0, 0, 0])
+    }
+
+    /// Lower 64 bits.
+    pub fn low_u64(&self) -> u64 {
+        self.0[0]
+    }
+
+    pub fn from_le_bytes(bytes: [u8; 32]) -> U256 {
+        let mut limbs = [0; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let mut b = [0; 8];
+            b.copy_from_slice(&bytes[i * 8..i * 8 + 8]);
+            *limb = u64::from_le_bytes(b);
+        }
+        U256(limbs)
+    }
+
+    pub fn to_le_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            bytes[i * 8..i * 8 + 8].copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_be_bytes(mut bytes: [u8; 32]) -> U256 {
+        bytes.reverse();
+        U256::from_le_bytes(bytes)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = self.to_le_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == U256::ZERO
+    }
+
+    /// Number of bits needed to represent `self`; position of the highest set bit plus one.
+    pub fn bits(&self) -> u32 {
+        for i in (0..4).rev() {
+            if self.0[i] != 0 {
+                return i as u32 * 64 + 64 - self.0[i].leading_zeros();
+            }
+        }
+        0
+    }
+
+    fn bit(&self, i: u32) -> bool {
+        self.0[(i / 64) as usize] >> (i % 64) & 1 == 1
+    }
+
+    pub fn overflowing_add(self, rhs: U256) -> (U256, bool) {
+        let mut limbs = [0; 4];
+        let mut carry = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        (U256(limbs), carry)
+    }
+
+    pub fn overflowing_sub(self, rhs: U256) -> (U256, bool) {
+        let mut limbs = [0; 4];
+        let mut borrow = false;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        (U256(limbs), borrow)
+    }
+
+    pub fn overflowing_mul(self, rhs: U256) -> (U256, bool) {
+        // Schoolbook multiplication into 8 limbs.
+        let mut wide = [0u64; 8];
+        for i in 0..4 {
+            let mut carry: u128 = 0;
+            for j in 0..4 {
+                let t = self.0[i] as u128 * rhs.0[j] as u128 + wide[i + j] as u128 + carry;
+                wide[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            wide[i + 4] = carry as u64;
+        }
+        let overflow = wide[4..].iter().any(|limb| *limb != 0);
+        (U256([wide[0], wide[1], wide[2], wide[3]]), overflow)
+    }
+
+    pub fn checked_add(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_add(rhs) {
+            (n, false) => Some(n),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_sub(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_sub(rhs) {
+            (n, false) => Some(n),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_mul(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_mul(rhs) {
+            (n, false) => Some(n),
+            (_, true) => None,
+        }
+    }
+
+    /// Quotient and remainder. `None` if `rhs` is zero.
+    pub fn checked_div_rem(self, rhs: U256) -> Option<(U256, U256)> {
+        if rhs.is_zero() {
+            return None;
+        }
+        if self < rhs {
+            return Some((U256::ZERO, self));
+        }
+
+        // Binary long division.
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for i in (0..self.bits()).rev() {
+            remainder = remainder << 1;
+            remainder.0[0] |= self.bit(i) as u64;
+            if remainder >= rhs {
+                remainder = remainder - rhs;
+                quotient.0[(i / 64) as usize] |= 1 << (i % 64);
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    pub fn checked_div(self, rhs: U256) -> Option<U256> {
+        self.checked_div_rem(rhs).map(|(q, _)| q)
+    }
+
+    /// Decode compact form used in `nBits` of a block header.
+    ///
+    /// Highest byte is the length of the number in bytes and lower 3 bytes are
+    /// the most significant bytes of it. `0x00800000` is a sign bit.
+    pub fn from_compact(compact: u32) -> Result<U256, CompactError> {
+        let size = compact >> 24;
+        let mut word = compact & 0x007f_ffff;
+        let n = if size <= 3 {
+            word >>= 8 * (3 - size);
+            U256::from_u64(word as u64)
+        } else {
+            U256::from_u64(word as u64) << (8 * (size - 3))
+        };
+
+        if word != 0 && compact & 0x0080_0000 != 0 {
+            return Err(CompactError::Negative);
+        }
+        if word != 0 && (size > 34 || (word > 0xff && size > 33) || (word > 0xffff && size > 32)) {
+            return Err(CompactError::Overflow);
+        }
+        Ok(n)
+    }
+
+    /// Encode into compact form. Lower bits which do not fit are truncated.
+    pub fn to_compact(&self) -> u32 {
+        let mut size = self.bits().div_ceil(8);
+        let mut compact = if size <= 3 {
+            (self.low_u64() << (8 * (3 - size))) as u32
+        } else {
+            (*self >> (8 * (size - 3))).low_u64() as u32
+        };
+
+        // The sign bit must not be set, so make the mantissa shorter.
+        if compact & 0x0080_0000 != 0 {
+            compact >>= 8;
+            size += 1;
+        }
+        compact | (size << 24)
+    }
+
+    /// Expected number of hashes to find a block whose hash is not above `self` as target;
+    /// `2^256 / (self + 1)`. Summing it up gives chainwork.
+    /// Zero target has no work, as Bitcoin Core's `GetBlockProof`.
+    pub fn work(&self) -> U256 {
+        if self.is_zero() {
+            return U256::ZERO;
+        }
+        // 2^256 can not be represented, but `2^256 / (t + 1)` equals
+        // `(2^256 - t - 1) / (t + 1) + 1`, and `2^256 - t - 1` is `!t`.
+        match self.checked_add(U256::ONE) {
+            Some(t1) => !*self / t1 + U256::ONE,
+            None => U256::ONE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactError {
+    Negative,
+    Overflow,
+}
+
+impl Display for CompactError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            CompactError::Negative => write!(f, "negative compact target"),
+            CompactError::Overflow => write!(f, "compact target overflows 256 bits"),
+        }
+    }
+}
+
+impl Error for CompactError {}
+
+impl From<u64> for U256 {
+    fn from(n: u64) -> U256 {
+        U256::from_u64(n)
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &U256) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &U256) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for U256 {
+    type Output = U256;
+
+    fn add(self, rhs: U256) -> U256 {
+        self.checked_add(rhs).expect("U256 addition overflow")
+    }
+}
+
+impl Sub for U256 {
+    type Output = U256;
+
+    fn sub(self, rhs: U256) -> U256 {
+        self.checked_sub(rhs).expect("U256 subtraction overflow")
+    }
+}
+
+impl Mul for U256 {
+    type Output = U256;
+
+    fn mul(self, rhs: U256) -> U256 {
+        self.checked_mul(rhs).expect("U256 multiplication overflow")
+    }
+}
+
+impl Div for U256 {
+    type Output = U256;
+
+    fn div(self, rhs: U256) -> U256 {
+        self.checked_div(rhs).expect("U256 division by zero")
+    }
+}
+
+impl Rem for U256 {
+    type Output = U256;
+
+    fn rem(self, rhs: U256) -> U256 {
+        self.checked_div_rem(rhs).expect("U256 division by zero").1
+    }
+}
+
+impl Not for U256 {
+    type Output = U256;
+
+    fn not(self) -> U256 {
+        U256([!self.0[0], !self.0[1], !self.0[2], !self.0[3]])
+    }
+}
+
+/// Bits shifted out are discarded. Shifting by 256 or more gives zero.
+impl Shl<u32> for U256 {
+    type Output = U256;
+
+    fn shl(self, shift: u32) -> U256 {
+        let mut limbs = [0; 4];
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        for (i, limb) in limbs.iter_mut().enumerate().skip(limb_shift) {
+            *limb = self.0[i - limb_shift] << bit_shift;
+            if bit_shift > 0 && i > limb_shift {
+                *limb |= self.0[i - limb_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        U256(limbs)
+    }
+}
+
+/// Shifting by 256 or more gives zero.
+impl Shr<u32> for U256 {
+    type Output = U256;
+
+    fn shr(self, shift: u32) -> U256 {
+        let mut limbs = [0; 4];
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        for (i, limb) in limbs.iter_mut().enumerate().take(4usize.saturating_sub(limb_shift)) {
+            *limb = self.0[i + limb_shift] >> bit_shift;
+            if bit_shift > 0 && i + limb_shift < 3 {
+                *limb |= self.0[i + limb_shift + 1] << (64 - bit_shift);
+            }
+        }
+        U256(limbs)
+    }
+}
+
+/// 64 hex digits with leading zeros, as Bitcoin Core shows chainwork.
+impl LowerHex for U256 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        for limb in self.0.iter().rev() {
+            write!(f, "{:016x}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for U256 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "U256(0x{:x})", self)
+    }
+}
+
+/// Decimal number.
+impl Display for U256 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        const TEN_19: u64 = 10_000_000_000_000_000_000;
+
+        // Split into chunks of 19 decimal digits, least significant first.
+        let mut chunks = Vec::new();
+        let mut n = *self;
+        loop {
+            let (q, r) = n.checked_div_rem(U256::from_u64(TEN_19)).unwrap();
+            chunks.push(r.low_u64());
+            n = q;
+            if n.is_zero() {
+                break;
+            }
+        }
+
+        let mut chunks = chunks.iter().rev();
+        write!(f, "{}", chunks.next().unwrap())?;
+        for chunk in chunks {
+            write!(f, "{:019}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+/// Little endian, as `uint256` is serialized.
+impl EncodableSized for U256 {
+    const SIZE: usize = 32;
+    type Array = [u8; 32];
+
+    fn bytes(&self) -> [u8; 32] {
+        self.to_le_bytes()
+    }
+}
+
+impl Decodable for U256 {
+    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<U256, DecodeError> {
+        Ok(U256::from_le_bytes(buf.read::<[u8; 32]>()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encode::Encodable;
+
+    fn u(hex: &str) -> U256 {
+        let mut bytes = [0; 32];
+        let padded = format!("{:0>64}", hex);
+        ::hex::decode_to_slice(&padded, &mut bytes).unwrap();
+        U256::from_be_bytes(bytes)
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = u("fedcba9876543210fedcba9876543210fedcba9876543210");
+        let b = u("123456789abcdef0123456789abcdef");
+
+        assert_eq!(a + b, u("fedcba9876543210ffffffffffffffffffffffffffffffff"));
+        assert_eq!(a - b, u("fedcba9876543210fdb97530eca86421fdb97530eca86421"));
+        assert_eq!(b * b, u("14b66dc33f6acdca878d6495a927ab94d0f77fe1940eedca5e20890f2a521"));
+        assert_eq!(a.checked_mul(b), None);
+        assert_eq!(a / b, u("e0000000000000d2f0"));
+        assert_eq!(a % b, u("e1f0fedcba9876551400"));
+        assert_eq!((a / b) * b + a % b, a);
+
+        assert_eq!(U256::MAX.checked_add(U256::ONE), None);
+        assert_eq!(U256::ZERO.overflowing_sub(U256::ONE), (U256::MAX, true));
+        assert_eq!(U256::MAX.checked_mul(U256::from_u64(2)), None);
+        assert_eq!(a.checked_div(U256::ZERO), None);
+
+        assert!(a > b);
+        assert!(U256::MAX > u("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"));
+        assert_eq!(a.bits(), 192);
+        assert_eq!(U256::ZERO.bits(), 0);
+    }
+
+    #[test]
+    fn shift() {
+        let one = U256::ONE;
+        assert_eq!(one << 255, u("8000000000000000000000000000000000000000000000000000000000000000"));
+        assert_eq!(one << 256, U256::ZERO);
+        assert_eq!((one << 200) >> 137, one << 63);
+        assert_eq!(U256::MAX >> 255, one);
+        assert_eq!(U256::MAX >> 256, U256::ZERO);
+        assert_eq!(u("123456789abcdef0") << 4, u("123456789abcdef00"));
+        assert_eq!(u("123456789abcdef00") >> 64, u("1"));
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(U256::ZERO.to_string(), "0");
+        assert_eq!(
+            U256::MAX.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(
+            format!("{:x}", U256::from_u64(0x100010001)),
+            "0000000000000000000000000000000000000000000000000000000100010001"
+        );
+    }
+
+    #[test]
+    fn compact() {
+        // Vectors from Bitcoin Core's arith_uint256_tests.
+        let zeros = [
+            0x0000_0000u32, 0x0012_3456, 0x0100_3456, 0x0200_0056, 0x0300_0000, 0x0400_0000,
+            0x0092_3456, 0x0180_3456, 0x0280_0056, 0x0380_0000, 0x0480_0000,
+        ];
+        for &compact in zeros.iter() {
+            assert_eq!(U256::from_compact(compact), Ok(U256::ZERO));
+        }
+        assert_eq!(U256::ZERO.to_compact(), 0);
+
+        let roundtrip = [
+            (0x0112_3456, "12", 0x0112_0000),
+            (0x0212_3456, "1234", 0x0212_3400),
+            (0x0312_3456, "123456", 0x0312_3456),
+            (0x0412_3456, "12345600", 0x0412_3456),
+            (0x0500_9234, "92340000", 0x0500_9234),
+            (0x2012_3456, "1234560000000000000000000000000000000000000000000000000000000000", 0x2012_3456),
+            (0x1d00_ffff, "ffff0000000000000000000000000000000000000000000000000000", 0x1d00_ffff),
+        ];
+        for &(compact, hex, expected) in roundtrip.iter() {
+            let n = U256::from_compact(compact).unwrap();
+            assert_eq!(n, u(hex));
+            assert_eq!(n.to_compact(), expected);
+        }
+        assert_eq!(U256::from_u64(0x80).to_compact(), 0x0200_8000);
+
+        assert_eq!(U256::from_compact(0x01fe_dcba), Err(CompactError::Negative));
+        assert_eq!(U256::from_compact(0x0492_3456), Err(CompactError::Negative));
+        assert_eq!(U256::from_compact(0xff12_3456), Err(CompactError::Overflow));
+        assert_eq!(U256::from_compact(0x2201_0000), Err(CompactError::Overflow));
+        assert_eq!(U256::from_compact(0x2200_00ff), Ok(U256::from_u64(0xff) << 248));
+    }
+
+    #[test]
+    fn work() {
+        // Work of the genesis block.
+        let target = U256::from_compact(0x1d00_ffff).unwrap();
+        assert_eq!(target.work(), U256::from_u64(0x1_0001_0001));
+        assert_eq!(U256::MAX.work(), U256::ONE);
+        assert_eq!(U256::ZERO.work(), U256::ZERO);
+        assert_eq!(U256::ONE.work(), U256::ONE << 255);
+    }
+
+    #[test]
+    fn encode_little_endian() {
+        let n = U256::from_u64(0x0102);
+        let bytes = n.to_vec();
+        assert_eq!(&bytes[..3], &[2, 1, 0]);
+        assert_eq!(U256::from_hex(&n.to_hex()).unwrap(), n);
+    }
+}
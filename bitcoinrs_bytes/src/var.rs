@@ -0,0 +1,228 @@
+use std::cmp::min;
+use std::mem::size_of;
+
+use decode::{Decodable, DecodeError, DecodeErrorKind, ReadBuffer, MAX_VECTOR_ALLOCATE};
+use encode::{Encodable, WriteBuffer};
+use endian::{u16_l, u32_l, u64_l};
+
+/// Variable length integer used as a length prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CompactSize(pub u64);
+
+impl Encodable for CompactSize {
+    fn length(&self) -> usize {
+        if self.0 < 0xFD {
+            1
+        } else if self.0 <= 0xFFFF {
+            3
+        } else if self.0 <= 0xFFFF_FFFF {
+            5
+        } else {
+            9
+        }
+    }
+
+    fn encode<W: WriteBuffer>(&self, buf: &mut W) {
+        if self.0 < 0xFD {
+            buf.write(self.0 as u8);
+        } else if self.0 <= 0xFFFF {
+            buf.write(0xFD_u8);
+            buf.write(u16_l::new(self.0 as u16));
+        } else if self.0 <= 0xFFFF_FFFF {
+            buf.write(0xFE_u8);
+            buf.write(u32_l::new(self.0 as u32));
+        } else {
+            buf.write(0xFF_u8);
+            buf.write(u64_l::new(self.0));
+        }
+    }
+}
+
+impl Decodable for CompactSize {
+    /// In strict context, value must be encoded in the shortest form.
+    fn decode<R: ReadBuffer>(bytes: &mut R) -> Result<CompactSize, DecodeError> {
+        let first = bytes.read::<u8>()?;
+        let (n, min_value) = if first < 0xFD {
+            (first as u64, 0)
+        } else if first == 0xFD {
+            (bytes.read::<u16_l>()?.value() as u64, 0xFD)
+        } else if first == 0xFE {
+            (bytes.read::<u32_l>()?.value() as u64, 0x1_0000)
+        } else {
+            (bytes.read::<u64_l>()?.value(), 0x1_0000_0000)
+        };
+
+        if bytes.context().strict && n < min_value {
+            return Err(DecodeErrorKind::NonCanonicalCompactSize.into());
+        }
+        Ok(CompactSize(n))
+    }
+}
+
+/// Vector encoded as a `CompactSize` count followed by its items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VarVec<T>(pub Vec<T>);
+
+impl<T: Encodable> Encodable for VarVec<T> {
+    fn length(&self) -> usize {
+        let items_len: usize = self.0.iter().map(|item| item.length()).sum();
+        CompactSize(self.0.len() as u64).length() + items_len
+    }
+
+    fn encode<W: WriteBuffer>(&self, buf: &mut W) {
+        buf.write(CompactSize(self.0.len() as u64));
+        for item in self.0.iter() {
+            item.encode(buf);
+        }
+    }
+}
+
+impl<T: Decodable> Decodable for VarVec<T> {
+    /// Total size of items the count claims is checked against `DecodeContext::max_alloc`.
+    /// Memory is reserved in chunks of `MAX_VECTOR_ALLOCATE` bytes as items are decoded,
+    /// so a lying count fails with `ShortBuffer` before allocating much.
+    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<VarVec<T>, DecodeError> {
+        let count = buf.read::<CompactSize>()?.0;
+        buf.context().check_alloc(count.saturating_mul(size_of::<T>().max(1) as u64))?;
+
+        let mut items = Vec::new();
+        decode_items(buf, count as usize, &mut items)?;
+        Ok(VarVec(items))
+    }
+}
+
+/// Decode `count` items into `items`, reserving memory chunk by chunk.
+fn decode_items<T: Decodable, R: ReadBuffer>(
+    buf: &mut R,
+    count: usize,
+    items: &mut Vec<T>,
+) -> Result<(), DecodeError> {
+    let chunk_len = MAX_VECTOR_ALLOCATE / size_of::<T>().max(1);
+    while items.len() < count {
+        let chunk = min(count - items.len(), chunk_len);
+        items.reserve_exact(chunk);
+        for _ in 0..chunk {
+            items.push(buf.read::<T>()?);
+        }
+    }
+    Ok(())
+}
+
+impl<T> From<Vec<T>> for VarVec<T> {
+    fn from(vec: Vec<T>) -> VarVec<T> {
+        VarVec(vec)
+    }
+}
+
+impl<T> From<VarVec<T>> for Vec<T> {
+    fn from(vec: VarVec<T>) -> Vec<T> {
+        vec.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use decode::DecodeContext;
+    use std::io::Cursor;
+
+    #[test]
+    fn compact_size_roundtrip() {
+        let cases: &[(u64, &[u8])] = &[
+            (0xFC, &[0xFC]),
+            (0xFD, &[0xFD, 0xFD, 0x00]),
+            (0x1_0000, &[0xFE, 0x00, 0x00, 0x01, 0x00]),
+            (0x1_0000_0000, &[0xFF, 0, 0, 0, 0, 1, 0, 0, 0]),
+        ];
+        for &(n, bytes) in cases {
+            assert_eq!(CompactSize(n).to_vec(), bytes);
+            assert_eq!(Cursor::new(bytes).read::<CompactSize>(), Ok(CompactSize(n)));
+        }
+    }
+
+    #[test]
+    fn strict_compact_size() {
+        let non_canonical = [0xFD, 0x01, 0x00];
+        assert_eq!(Cursor::new(&non_canonical[..]).read::<CompactSize>(), Ok(CompactSize(1)));
+
+        let e = Cursor::new(&non_canonical[..])
+            .with_context(DecodeContext::strict())
+            .read::<CompactSize>()
+            .unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::NonCanonicalCompactSize);
+        assert_eq!(e.offset(), Some(0));
+
+        let canonical = [0xFE, 0x00, 0x00, 0x01, 0x00];
+        let mut buf = Cursor::new(&canonical[..]).with_context(DecodeContext::strict());
+        assert_eq!(buf.read::<CompactSize>(), Ok(CompactSize(0x1_0000)));
+    }
+
+    #[test]
+    fn var_vec_limits() {
+        // Claims u64::MAX items.
+        let bytes = [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01];
+        let e = Cursor::new(&bytes[..]).read::<VarVec<u8>>().unwrap_err();
+        match e.kind() {
+            DecodeErrorKind::OversizeLength { .. } => {}
+            kind => panic!("unexpected error kind {:?}", kind),
+        }
+
+        // Claims as many bytes as allowed but has only one item.
+        let bytes = [0xFE, 0x00, 0x00, 0x40, 0x00, 0x01, 0, 0, 0, 0, 0, 0, 0];
+        let e = Cursor::new(&bytes[..]).read::<VarVec<u64_l>>().unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::ShortBuffer);
+
+        // Count is checked in bytes, not in items.
+        let bytes = [0xFE, 0x01, 0x00, 0x40, 0x00];
+        let e = Cursor::new(&bytes[..]).read::<VarVec<u64_l>>().unwrap_err();
+        assert_eq!(
+            e.kind(),
+            DecodeErrorKind::OversizeLength {
+                len: 0x40_0001 * 8,
+                max: 0x0200_0000,
+            }
+        );
+
+        let ctx = DecodeContext {
+            max_size: 4,
+            ..DecodeContext::default()
+        };
+        let bytes = [4, 1, 2, 3, 4];
+        let e = Cursor::new(&bytes[..])
+            .with_context(ctx)
+            .read::<VarVec<u8>>()
+            .unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::SizeLimitExceeded { max: 4 });
+    }
+
+    #[test]
+    fn var_vec_allocates_by_chunk() {
+        // 5 bytes claim 32 MiB of items.
+        let bytes = [0xFE, 0x00, 0x00, 0x00, 0x02, 0x01];
+        let mut buf = Cursor::new(&bytes[..]);
+        let count = buf.read::<CompactSize>().unwrap().0 as usize;
+        assert_eq!(count, 0x0200_0000);
+
+        let mut items = Vec::<u8>::new();
+        let e = decode_items(&mut buf, count, &mut items).unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::ShortBuffer);
+        assert_eq!(items, [1]);
+        assert!(items.capacity() <= MAX_VECTOR_ALLOCATE);
+
+        // More than a chunk is decoded if the bytes are really there.
+        let len = MAX_VECTOR_ALLOCATE + 3;
+        let mut bytes = CompactSize(len as u64).to_vec();
+        bytes.resize(bytes.len() + len, 7);
+        let vec = Cursor::new(bytes.as_slice()).read::<VarVec<u8>>().unwrap();
+        assert_eq!(vec.0.len(), len);
+    }
+
+    #[test]
+    fn var_vec_roundtrip() {
+        let vec = VarVec(vec![VarVec(vec![1_u8, 2]), VarVec(vec![]), VarVec(vec![3])]);
+        let bytes = vec.to_vec();
+        assert_eq!(bytes, [3, 2, 1, 2, 0, 1, 3]);
+        assert_eq!(bytes.len(), vec.length());
+        assert_eq!(Cursor::new(bytes.as_slice()).read::<VarVec<VarVec<u8>>>(), Ok(vec));
+    }
+}
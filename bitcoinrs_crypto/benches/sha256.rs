@@ -3,7 +3,7 @@ extern crate test;
 use test::Bencher;
 
 extern crate bitcoinrs_crypto;
-use bitcoinrs_crypto::sha256;
+use bitcoinrs_crypto::{sha256, sha256d_64};
 
 #[bench]
 fn sha256_with_empty_msg(b: &mut Bencher) {
@@ -22,3 +22,27 @@ fn sha256_with_large_msg(b: &mut Bencher) {
     let msg: [u8; 10000] = [0; 10000];
     b.iter(|| sha256(&msg[..]));
 }
+
+#[bench]
+fn sha256_with_1mb_msg(b: &mut Bencher) {
+    let msg = vec![0; 1_000_000];
+    b.iter(|| sha256(&msg[..]));
+}
+
+#[bench]
+fn sha256d_of_1000_nodes_one_by_one(b: &mut Bencher) {
+    let input = vec![[0x5a; 64]; 1000];
+    b.iter(|| {
+        input
+            .iter()
+            .map(|msg| sha256(&sha256(&msg[..])))
+            .collect::<Vec<_>>()
+    });
+}
+
+#[bench]
+fn sha256d_of_1000_nodes_at_once(b: &mut Bencher) {
+    let input = vec![[0x5a; 64]; 1000];
+    let mut output = vec![[0; 32]; 1000];
+    b.iter(|| sha256d_64(&mut output, &input));
+}
@@ -0,0 +1,156 @@
+use std::error::Error;
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use bitcoinrs_bytes::base58::{self, Base58Error};
+
+use hash::sha256d;
+
+const CHECKSUM_SIZE: usize = 4;
+
+/// Encode `data` followed by its checksum into Base58 string.
+pub fn encode(data: &[u8]) -> String {
+    let mut bytes = Vec::with_capacity(data.len() + CHECKSUM_SIZE);
+    bytes.extend_from_slice(data);
+    bytes.extend_from_slice(&checksum(data));
+    base58::encode(&bytes)
+}
+
+/// Decode Base58Check string and return data without its checksum.
+/// Strings longer than `base58::MAX_LENGTH` are rejected before decoding.
+pub fn decode(s: &str) -> Result<Vec<u8>, Base58CheckError> {
+    let mut bytes = base58::decode(s)?;
+    if bytes.len() < CHECKSUM_SIZE {
+        return Err(Base58CheckError::TooShort(bytes.len()));
+    }
+
+    let data_len = bytes.len() - CHECKSUM_SIZE;
+    let mut expected = [0; CHECKSUM_SIZE];
+    expected.copy_from_slice(&bytes[data_len..]);
+    let actual = checksum(&bytes[..data_len]);
+    if expected != actual {
+        return Err(Base58CheckError::BadChecksum { expected, actual });
+    }
+
+    bytes.truncate(data_len);
+    Ok(bytes)
+}
+
+/// Encode `version` bytes followed by `payload`.
+/// Addresses and WIF keys have 1 byte version, and extended keys have 4 bytes version.
+pub fn encode_versioned(version: &[u8], payload: &[u8]) -> String {
+    let mut data = Vec::with_capacity(version.len() + payload.len());
+    data.extend_from_slice(version);
+    data.extend_from_slice(payload);
+    encode(&data)
+}
+
+/// Decode Base58Check string into `version_len` bytes version and `payload_len` bytes payload.
+pub fn decode_versioned(
+    s: &str,
+    version_len: usize,
+    payload_len: usize,
+) -> Result<(Vec<u8>, Vec<u8>), Base58CheckError> {
+    let mut data = decode(s)?;
+    if data.len() != version_len + payload_len {
+        return Err(Base58CheckError::InvalidLength {
+            expected: version_len + payload_len,
+            actual: data.len(),
+        });
+    }
+    let payload = data.split_off(version_len);
+    Ok((data, payload))
+}
+
+fn checksum(data: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let hash = sha256d(data);
+    let mut checksum = [0; CHECKSUM_SIZE];
+    checksum.copy_from_slice(&hash[..CHECKSUM_SIZE]);
+    checksum
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base58CheckError {
+    Base58(Base58Error),
+    /// Decoded bytes are shorter than checksum.
+    TooShort(usize),
+    BadChecksum {
+        expected: [u8; CHECKSUM_SIZE],
+        actual: [u8; CHECKSUM_SIZE],
+    },
+    /// Length of version and payload is not the expected one.
+    InvalidLength { expected: usize, actual: usize },
+}
+
+impl From<Base58Error> for Base58CheckError {
+    fn from(e: Base58Error) -> Base58CheckError {
+        Base58CheckError::Base58(e)
+    }
+}
+
+impl Display for Base58CheckError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            Base58CheckError::Base58(ref e) => write!(f, "{}", e),
+            Base58CheckError::TooShort(len) => {
+                write!(f, "{} bytes are too short to have checksum", len)
+            }
+            Base58CheckError::BadChecksum { expected, actual } => write!(
+                f,
+                "bad checksum; expected {:?} but computed {:?}",
+                expected, actual
+            ),
+            Base58CheckError::InvalidLength { expected, actual } => {
+                write!(f, "invalid length {}; expected {}", actual, expected)
+            }
+        }
+    }
+}
+
+impl Error for Base58CheckError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinrs_bytes::hex;
+
+    const ADDRESS: &str = "16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvM";
+    const PUBKEY_HASH: &str = "010966776006953d5567439e5e39f86a0d273bee";
+
+    #[test]
+    fn p2pkh_address() {
+        let hash = hex::decode(PUBKEY_HASH).unwrap();
+        assert_eq!(encode_versioned(&[0x00], &hash), ADDRESS);
+        assert_eq!(
+            decode_versioned(ADDRESS, 1, 20).unwrap(),
+            (vec![0x00], hash)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            decode_versioned(ADDRESS, 4, 20),
+            Err(Base58CheckError::InvalidLength {
+                expected: 24,
+                actual: 21
+            })
+        );
+        match decode("16UwLL9Risc3QfPqBUvKofHmBQ7wMtjvN") {
+            Err(Base58CheckError::BadChecksum { .. }) => {}
+            res => panic!("unexpected result {:?}", res),
+        }
+        assert_eq!(decode("2g"), Err(Base58CheckError::TooShort(1)));
+        assert_eq!(
+            decode("0"),
+            Err(Base58CheckError::Base58(Base58Error::InvalidChar {
+                c: '0',
+                index: 0
+            }))
+        );
+        let long = ADDRESS.repeat(4);
+        assert_eq!(
+            decode(&long),
+            Err(Base58CheckError::Base58(Base58Error::TooLong(long.len())))
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt::{Debug, Display, Error as FmtError, Formatter};
+use std::str::FromStr;
+
+use bitcoinrs_bytes::decode::{Decodable, DecodeError, ReadBuffer};
+use bitcoinrs_bytes::encode::EncodableSized;
+use bitcoinrs_bytes::hex::{self, HexError, Hex, ReversedHex};
+
+use ripemd160::ripemd160;
+use sha2::{sha256, Sha256};
+
+/// Streaming hash function, which `Hmac` is built over.
+pub trait HashEngine: Clone + Default {
+    type Output: AsRef<[u8]> + AsMut<[u8]> + Copy;
+
+    /// Size of a message block in bytes.
+    const BLOCK_SIZE: usize;
+
+    /// Size of a hash value in bytes.
+    const OUTPUT_SIZE: usize;
+
+    fn update(&mut self, bytes: &[u8]);
+
+    fn finalize(self) -> Self::Output;
+}
+
+/// Double SHA-256, used for txids, block hashes and checksums.
+pub fn sha256d(msg: &[u8]) -> [u8; 32] {
+    sha256(&sha256(msg))
+}
+
+/// RIPEMD-160 of SHA-256, used for P2PKH, P2SH and P2WPKH.
+pub fn hash160(msg: &[u8]) -> [u8; 20] {
+    ripemd160(&sha256(msg))
+}
+
+/// Define a newtype of hash value.
+/// `reversed` types are shown in reversed hex as Bitcoin Core does for txids and block hashes,
+/// and `forward` types are shown as they are.
+macro_rules! hash_newtype {
+    ($name: ident, $len: expr, $display: ident, $doc: expr) => {
+        #[doc = $doc]
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+        pub struct $name([u8; $len]);
+
+        impl $name {
+            pub const LEN: usize = $len;
+
+            pub fn from_bytes(bytes: [u8; $len]) -> $name {
+                $name(bytes)
+            }
+
+            /// Bytes in the internal order, not in the displayed order.
+            pub fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> $name {
+                $name(bytes)
+            }
+        }
+
+        impl From<$name> for [u8; $len] {
+            fn from(hash: $name) -> [u8; $len] {
+                hash.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl EncodableSized for $name {
+            const SIZE: usize = $len;
+            type Array = [u8; $len];
+
+            fn bytes(&self) -> [u8; $len] {
+                self.0
+            }
+        }
+
+        impl Decodable for $name {
+            fn decode<R: ReadBuffer>(buf: &mut R) -> Result<$name, DecodeError> {
+                Ok($name(buf.read::<[u8; $len]>()?))
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+                hash_newtype!(@fmt $display, self, f)
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+                write!(f, "{}(", stringify!($name))?;
+                hash_newtype!(@fmt $display, self, f)?;
+                write!(f, ")")
+            }
+        }
+
+        /// Parse hex string in the displayed order.
+        impl FromStr for $name {
+            type Err = HexError;
+
+            fn from_str(s: &str) -> Result<$name, HexError> {
+                let mut bytes = [0; $len];
+                hex::decode_to_slice(s, &mut bytes)?;
+                hash_newtype!(@parsed $display, bytes);
+                Ok($name(bytes))
+            }
+        }
+    };
+    (@fmt reversed, $self: ident, $f: ident) => {
+        Display::fmt(&ReversedHex(&$self.0), $f)
+    };
+    (@fmt forward, $self: ident, $f: ident) => {
+        Display::fmt(&Hex(&$self.0), $f)
+    };
+    (@parsed reversed, $bytes: ident) => {
+        $bytes.reverse()
+    };
+    (@parsed forward, $bytes: ident) => {};
+}
+
+/// Engine of BIP340 tagged hash, `SHA256(SHA256(tag) || SHA256(tag) || msg)`.
+/// Well-known tags have their own types which start from a precomputed midstate instead.
+pub fn tagged_engine(tag: &str) -> Sha256 {
+    let tag_hash = sha256(tag.as_bytes());
+    let mut engine = Sha256::new();
+    engine.update(&tag_hash);
+    engine.update(&tag_hash);
+    engine
+}
+
+pub fn tagged_hash(tag: &str, msg: &[u8]) -> [u8; 32] {
+    let mut engine = tagged_engine(tag);
+    engine.update(msg);
+    engine.finalize()
+}
+
+/// Define a newtype of double SHA-256 hash value.
+macro_rules! sha256d_newtype {
+    ($name: ident, $doc: expr) => {
+        hash_newtype!($name, 32, reversed, $doc);
+
+        impl $name {
+            pub fn hash(msg: &[u8]) -> $name {
+                $name(sha256d(msg))
+            }
+
+            /// Finish hashing bytes written to `engine` so far.
+            /// Any `Encodable` can be written to it without encoding it into a buffer.
+            pub fn from_engine(engine: Sha256) -> $name {
+                $name(sha256(&engine.finalize()))
+            }
+        }
+    };
+}
+
+/// Define a newtype of tagged hash value.
+/// `$midstate` is the midstate after the 64 bytes prefix of `$tag`.
+macro_rules! tagged_newtype {
+    ($name: ident, $tag: expr, $midstate: expr, $doc: expr) => {
+        hash_newtype!($name, 32, forward, $doc);
+
+        impl $name {
+            pub const TAG: &'static str = $tag;
+
+            /// Engine which has already processed the tag prefix.
+            pub fn engine() -> Sha256 {
+                Sha256::from_midstate($midstate, 64)
+            }
+
+            pub fn hash(msg: &[u8]) -> $name {
+                let mut engine = $name::engine();
+                engine.update(msg);
+                $name(engine.finalize())
+            }
+
+            /// Finish hashing bytes written to an engine from `engine()`.
+            pub fn from_engine(engine: Sha256) -> $name {
+                $name(engine.finalize())
+            }
+        }
+    };
+}
+
+sha256d_newtype!(Sha256d, "Double SHA-256 hash value which is not a txid nor a block hash.");
+sha256d_newtype!(Txid, "Transaction id; double SHA-256 of a transaction without witness.");
+sha256d_newtype!(Wtxid, "Double SHA-256 of a transaction with witness.");
+sha256d_newtype!(BlockHash, "Double SHA-256 of a block header.");
+hash_newtype!(Hash160, 20, forward, "RIPEMD-160 of SHA-256, used for public key hashes and script hashes.");
+tagged_newtype!(
+    TapLeafHash,
+    "TapLeaf",
+    [
+        0x9c, 0xe0, 0xe4, 0xe6, 0x7c, 0x11, 0x6c, 0x39, 0x38, 0xb3, 0xca, 0xf2, 0xc3, 0x0f, 0x50, 0x89,
+        0xd3, 0xf3, 0x93, 0x6c, 0x47, 0x63, 0x6e, 0x60, 0x7d, 0xb3, 0x3e, 0xea, 0xdd, 0xc6, 0xf0, 0xc9,
+    ],
+    "BIP341 tagged hash of a tapscript leaf."
+);
+tagged_newtype!(
+    TapBranchHash,
+    "TapBranch",
+    [
+        0x23, 0xa8, 0x65, 0xa9, 0xb8, 0xa4, 0x0d, 0xa7, 0x97, 0x7c, 0x1e, 0x04, 0xc4, 0x9e, 0x24, 0x6f,
+        0xb5, 0xbe, 0x13, 0x76, 0x9d, 0x24, 0xc9, 0xb7, 0xb5, 0x83, 0xb5, 0xd4, 0xa8, 0xd2, 0x26, 0xd2,
+    ],
+    "BIP341 tagged hash of a pair of script tree nodes."
+);
+tagged_newtype!(
+    TapTweakHash,
+    "TapTweak",
+    [
+        0xd1, 0x29, 0xa2, 0xf3, 0x70, 0x1c, 0x65, 0x5d, 0x65, 0x83, 0xb6, 0xc3, 0xb9, 0x41, 0x97, 0x27,
+        0x95, 0xf4, 0xe2, 0x32, 0x94, 0xfd, 0x54, 0xf4, 0xa2, 0xae, 0x8d, 0x85, 0x47, 0xca, 0x59, 0x0b,
+    ],
+    "BIP341 tagged hash to tweak an internal key."
+);
+tagged_newtype!(
+    Bip340ChallengeHash,
+    "BIP0340/challenge",
+    [
+        0x9c, 0xec, 0xba, 0x11, 0x23, 0x92, 0x53, 0x81, 0x11, 0x67, 0x91, 0x12, 0xd1, 0x62, 0x7e, 0x0f,
+        0x97, 0xc8, 0x75, 0x50, 0x00, 0x3c, 0xc7, 0x65, 0x90, 0xf6, 0x11, 0x64, 0x33, 0xe9, 0xb6, 0x6a,
+    ],
+    "BIP340 challenge of a signature."
+);
+tagged_newtype!(
+    Bip340AuxHash,
+    "BIP0340/aux",
+    [
+        0x24, 0xdd, 0x32, 0x19, 0x4e, 0xba, 0x7e, 0x70, 0xca, 0x0f, 0xab, 0xb9, 0x0f, 0xa3, 0x16, 0x6d,
+        0x3a, 0xfb, 0xe4, 0xb1, 0x4c, 0x44, 0xdf, 0x97, 0x4a, 0xac, 0x27, 0x39, 0x24, 0x9e, 0x85, 0x0a,
+    ],
+    "BIP340 hash of auxiliary random data."
+);
+tagged_newtype!(
+    Bip340NonceHash,
+    "BIP0340/nonce",
+    [
+        0x46, 0x61, 0x5b, 0x35, 0xf4, 0xbf, 0xbf, 0xf7, 0x9f, 0x8d, 0xc6, 0x71, 0x83, 0x62, 0x7a, 0xb3,
+        0x60, 0x21, 0x71, 0x80, 0x57, 0x35, 0x86, 0x61, 0x21, 0xa2, 0x9e, 0x54, 0x68, 0xb0, 0x7b, 0x4c,
+    ],
+    "BIP340 hash to derive a nonce."
+);
+
+impl Hash160 {
+    pub fn hash(msg: &[u8]) -> Hash160 {
+        Hash160(hash160(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinrs_bytes::encode::{Encodable, WriteBuffer};
+    use std::io::Cursor;
+
+    const GENESIS_HEADER: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+
+    #[test]
+    fn genesis_block() {
+        let header = hex::decode(GENESIS_HEADER).unwrap();
+
+        let hash = BlockHash::hash(&header);
+        let hash_str = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+        assert_eq!(hash.to_string(), hash_str);
+        assert_eq!(format!("{:?}", hash), format!("BlockHash({})", hash_str));
+        assert_eq!(hash_str.parse::<BlockHash>(), Ok(hash));
+        assert_eq!(hash.as_bytes()[31], 0);
+
+        let mut engine = Sha256::new();
+        engine.write_bytes(&header);
+        assert_eq!(BlockHash::from_engine(engine), hash);
+
+        // Merkle root is the txid of the coinbase transaction.
+        let mut buf = Cursor::new(&header[36..]);
+        let merkle_root = buf.read::<Txid>().unwrap();
+        assert_eq!(
+            merkle_root.to_string(),
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+        assert_eq!(merkle_root.to_vec(), &header[36..68]);
+    }
+
+    #[test]
+    fn hash160_of_pubkey() {
+        // Compressed public key of secret key 1.
+        let pubkey = hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").unwrap();
+        assert_eq!(Hash160::hash(&pubkey).to_string(), "751e76e8199196d454941c45d1b3a323f1433bd6");
+    }
+
+    #[test]
+    fn tagged_midstates() {
+        fn check(midstate: [u8; 32], tag: &str) {
+            assert_eq!(midstate, tagged_engine(tag).midstate(), "{}", tag);
+        }
+        check(TapLeafHash::engine().midstate(), TapLeafHash::TAG);
+        check(TapBranchHash::engine().midstate(), TapBranchHash::TAG);
+        check(TapTweakHash::engine().midstate(), TapTweakHash::TAG);
+        check(Bip340ChallengeHash::engine().midstate(), Bip340ChallengeHash::TAG);
+        check(Bip340AuxHash::engine().midstate(), Bip340AuxHash::TAG);
+        check(Bip340NonceHash::engine().midstate(), Bip340NonceHash::TAG);
+
+        let hash = TapLeafHash::hash(&[]);
+        assert_eq!(hash.to_string(), "5212c288a377d1f8164962a5a13429f9ba6a7b84e59776a52c6637df2106facb");
+        assert_eq!(hash.as_bytes(), &tagged_hash("TapLeaf", &[]));
+        assert_eq!(TapTweakHash::hash(b"abc").as_bytes(), &tagged_hash("TapTweak", b"abc"));
+    }
+
+    #[test]
+    fn forward_display() {
+        let hash = Hash160::from_bytes([
+            0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54, 0x94, 0x1c, 0x45, 0xd1, 0xb3,
+            0xa3, 0x23, 0xf1, 0x43, 0x3b, 0xd6,
+        ]);
+        assert_eq!(hash.to_string(), "751e76e8199196d454941c45d1b3a323f1433bd6");
+        assert_eq!("751e76e8199196d454941c45d1b3a323f1433bd6".parse(), Ok(hash));
+        assert_eq!(
+            "751e76e8199196d454941c45d1b3a323f1433b".parse::<Hash160>(),
+            Err(HexError::InvalidLength {
+                expected: 40,
+                actual: 38,
+            })
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use bitcoinrs_bytes::encode::WriteBuffer;
+
+use hash::HashEngine;
+use sha2::Sha256;
+use sha512::Sha512;
+
+pub type HmacSha256 = Hmac<Sha256>;
+pub type HmacSha512 = Hmac<Sha512>;
+
+/// The largest block size among supported hash functions.
+const MAX_BLOCK_SIZE: usize = 128;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+pub fn hmac_sha256(key: &[u8], msg: &[u8]) -> [u8; 32] {
+    let mut engine = HmacSha256::new(key);
+    engine.update(msg);
+    engine.finalize()
+}
+
+pub fn hmac_sha512(key: &[u8], msg: &[u8]) -> [u8; 64] {
+    let mut engine = HmacSha512::new(key);
+    engine.update(msg);
+    engine.finalize()
+}
+
+/// Streaming HMAC engine (RFC 2104) over any `HashEngine`.
+///
+/// Like hash engines, it implements `WriteBuffer`.
+#[derive(Clone)]
+pub struct Hmac<H: HashEngine> {
+    inner: H,
+    outer: H,
+}
+
+impl<H: HashEngine> Hmac<H> {
+    pub fn new(key: &[u8]) -> Hmac<H> {
+        assert!(H::BLOCK_SIZE <= MAX_BLOCK_SIZE);
+
+        // Keys longer than a block are hashed first.
+        let mut key_block = [0; MAX_BLOCK_SIZE];
+        if key.len() > H::BLOCK_SIZE {
+            let mut engine = H::default();
+            engine.update(key);
+            let hashed = engine.finalize();
+            key_block[..hashed.as_ref().len()].copy_from_slice(hashed.as_ref());
+        } else {
+            key_block[..key.len()].copy_from_slice(key);
+        }
+        let key_block = &mut key_block[..H::BLOCK_SIZE];
+
+        let mut inner = H::default();
+        let mut outer = H::default();
+        for byte in key_block.iter_mut() {
+            *byte ^= IPAD;
+        }
+        inner.update(key_block);
+        for byte in key_block.iter_mut() {
+            *byte ^= IPAD ^ OPAD;
+        }
+        outer.update(key_block);
+
+        Hmac { inner, outer }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.inner.update(bytes);
+    }
+
+    pub fn finalize(self) -> H::Output {
+        let mut outer = self.outer;
+        outer.update(self.inner.finalize().as_ref());
+        outer.finalize()
+    }
+}
+
+impl<H: HashEngine> WriteBuffer for Hmac<H> {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+
+    fn has_buffer(&self, _size: usize) -> bool {
+        true
+    }
+}
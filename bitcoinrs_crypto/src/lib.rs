@@ -1,7 +1,19 @@
 extern crate bitcoinrs_bytes;
 
 mod sha2;
+mod sha512;
+mod ripemd160;
+mod hmac;
+mod pbkdf2;
 mod rand;
+pub mod base58check;
+pub mod hash;
+pub mod secp256k1;
 
-pub use self::sha2::sha256;
+pub use self::sha2::{sha256, sha256d_64, Sha256};
+pub use self::sha512::{sha512, Sha512};
+pub use self::ripemd160::{ripemd160, Ripemd160};
+pub use self::hmac::{hmac_sha256, hmac_sha512, Hmac, HmacSha256, HmacSha512};
+pub use self::pbkdf2::{pbkdf2, pbkdf2_hmac_sha512};
+pub use self::hash::{hash160, sha256d, tagged_hash, HashEngine};
 pub use self::rand::xorshift32;
@@ -0,0 +1,41 @@
+use hash::HashEngine;
+use hmac::Hmac;
+use sha512::Sha512;
+
+/// PBKDF2-HMAC-SHA512 (RFC 8018), which BIP39 uses to convert a mnemonic into a seed.
+/// Fills whole `output`.
+pub fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], iterations: u32, output: &mut [u8]) {
+    pbkdf2::<Sha512>(password, salt, iterations, output)
+}
+
+/// PBKDF2 with HMAC over any `HashEngine`. Fills whole `output`.
+///
+/// # Panic
+/// when `iterations` is zero.
+pub fn pbkdf2<H: HashEngine>(password: &[u8], salt: &[u8], iterations: u32, output: &mut [u8]) {
+    assert!(iterations > 0, "iterations must be positive");
+
+    // Key is hashed into HMAC state only once and the state is cloned at each iteration.
+    let prf = Hmac::<H>::new(password);
+
+    for (i, chunk) in output.chunks_mut(H::OUTPUT_SIZE).enumerate() {
+        let block_index = i as u32 + 1;
+
+        let mut engine = prf.clone();
+        engine.update(salt);
+        engine.update(&block_index.to_be_bytes());
+        let mut u = engine.finalize();
+        let mut block = u;
+
+        for _ in 1..iterations {
+            let mut engine = prf.clone();
+            engine.update(u.as_ref());
+            u = engine.finalize();
+            for (b, x) in block.as_mut().iter_mut().zip(u.as_ref()) {
+                *b ^= x;
+            }
+        }
+
+        chunk.copy_from_slice(&block.as_ref()[..chunk.len()]);
+    }
+}
@@ -0,0 +1,229 @@
+use bitcoinrs_bytes::encode::WriteBuffer;
+
+use hash::HashEngine;
+
+type Word = u32;
+type HashValue = [Word; 5];
+type MsgBlock = [Word; 16];
+
+const BLOCK_SIZE: usize = 512 / 8;
+
+pub fn ripemd160(msg: &[u8]) -> [u8; 20] {
+    let mut engine = Ripemd160::new();
+    engine.update(msg);
+    engine.finalize()
+}
+
+/* ===================================== */
+/* Engine */
+/* ===================================== */
+
+/// Streaming RIPEMD-160 engine.
+///
+/// Like `Sha256`, it implements `WriteBuffer`.
+#[derive(Clone)]
+pub struct Ripemd160 {
+    hash_val: HashValue,
+    /// Bytes which do not fill a message block yet.
+    block: [u8; BLOCK_SIZE],
+    /// Total length of input in bytes.
+    length: u64,
+}
+
+impl Ripemd160 {
+    pub fn new() -> Ripemd160 {
+        Ripemd160 {
+            hash_val: INIT_HASH_VAL,
+            block: [0; BLOCK_SIZE],
+            length: 0,
+        }
+    }
+
+    /// Length of input so far in bytes.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        let block_len = self.block_len();
+        self.length += bytes.len() as u64;
+
+        // Fill pending block first.
+        if block_len > 0 {
+            let n = (BLOCK_SIZE - block_len).min(bytes.len());
+            self.block[block_len..block_len + n].copy_from_slice(&bytes[..n]);
+            bytes = &bytes[n..];
+            if block_len + n < BLOCK_SIZE {
+                return;
+            }
+            let block = self.block;
+            self.process(&block);
+        }
+
+        // Process whole blocks without copying.
+        let mut chunks = bytes.chunks_exact(BLOCK_SIZE);
+        for chunk in &mut chunks {
+            self.process(chunk);
+        }
+        let rest = chunks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+    }
+
+    pub fn finalize(mut self) -> [u8; 20] {
+        let bit_len = self.length * 8;
+
+        // Same padding as SHA-256 except that length is little endian.
+        let block_len = self.block_len();
+        let zeros = (BLOCK_SIZE * 2 - block_len - 1 - 8) % BLOCK_SIZE;
+        let mut padding = [0; BLOCK_SIZE * 2];
+        padding[0] = 0b_1000_0000;
+        padding[1 + zeros..1 + zeros + 8].copy_from_slice(&bit_len.to_le_bytes());
+        self.update(&padding[..1 + zeros + 8]);
+        debug_assert!(self.block_len() == 0);
+
+        let mut result = [0; 20];
+        for (i, word) in self.hash_val.iter().enumerate() {
+            result[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+
+    fn block_len(&self) -> usize {
+        (self.length % BLOCK_SIZE as u64) as usize
+    }
+
+    fn process(&mut self, block: &[u8]) {
+        let mut msg_block: MsgBlock = [0; 16];
+        for (i, word) in msg_block.iter_mut().enumerate() {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&block[i * 4..i * 4 + 4]);
+            *word = Word::from_le_bytes(bytes);
+        }
+        compress(&mut self.hash_val, &msg_block);
+    }
+}
+
+impl Default for Ripemd160 {
+    fn default() -> Ripemd160 {
+        Ripemd160::new()
+    }
+}
+
+impl WriteBuffer for Ripemd160 {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+
+    fn has_buffer(&self, _size: usize) -> bool {
+        true
+    }
+}
+
+impl HashEngine for Ripemd160 {
+    type Output = [u8; 20];
+
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = 20;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Ripemd160::update(self, bytes);
+    }
+
+    fn finalize(self) -> [u8; 20] {
+        Ripemd160::finalize(self)
+    }
+}
+
+/* ===================================== */
+/* Computation */
+/* ===================================== */
+
+const INIT_HASH_VAL: HashValue = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// Constants of each 16 steps, for left and right lines.
+const K_LEFT: [Word; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+const K_RIGHT: [Word; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+/// Index of message word used at each step.
+const R_LEFT: [usize; 80] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+    7, 4, 13, 1, 10, 6, 15, 3, 12, 0, 9, 5, 2, 14, 11, 8,
+    3, 10, 14, 4, 9, 15, 8, 1, 2, 7, 0, 6, 13, 11, 5, 12,
+    1, 9, 11, 10, 0, 8, 12, 4, 13, 3, 7, 15, 14, 5, 6, 2,
+    4, 0, 5, 9, 7, 12, 2, 10, 14, 1, 3, 8, 11, 6, 15, 13,
+];
+const R_RIGHT: [usize; 80] = [
+    5, 14, 7, 0, 9, 2, 11, 4, 13, 6, 15, 8, 1, 10, 3, 12,
+    6, 11, 3, 7, 0, 13, 5, 10, 14, 15, 8, 12, 4, 9, 1, 2,
+    15, 5, 1, 3, 7, 14, 6, 9, 11, 8, 12, 2, 10, 0, 4, 13,
+    8, 6, 4, 1, 3, 11, 15, 0, 5, 12, 2, 13, 9, 7, 10, 14,
+    12, 15, 10, 4, 1, 5, 8, 7, 6, 2, 13, 14, 0, 3, 9, 11,
+];
+
+/// Amount of left rotation at each step.
+const S_LEFT: [u32; 80] = [
+    11, 14, 15, 12, 5, 8, 7, 9, 11, 13, 14, 15, 6, 7, 9, 8,
+    7, 6, 8, 13, 11, 9, 7, 15, 7, 12, 15, 9, 11, 7, 13, 12,
+    11, 13, 6, 7, 14, 9, 13, 15, 14, 8, 13, 6, 5, 12, 7, 5,
+    11, 12, 14, 15, 14, 15, 9, 8, 9, 14, 5, 6, 8, 6, 5, 12,
+    9, 15, 5, 11, 6, 8, 13, 12, 5, 12, 13, 14, 11, 8, 5, 6,
+];
+const S_RIGHT: [u32; 80] = [
+    8, 9, 9, 11, 13, 15, 15, 5, 7, 7, 8, 11, 14, 14, 12, 6,
+    9, 13, 15, 7, 12, 8, 9, 11, 7, 7, 12, 7, 6, 15, 13, 11,
+    9, 7, 15, 11, 8, 6, 6, 14, 12, 13, 5, 14, 13, 13, 7, 5,
+    15, 5, 8, 11, 14, 14, 6, 14, 6, 9, 12, 9, 12, 5, 15, 8,
+    8, 5, 12, 9, 12, 5, 14, 6, 8, 13, 6, 5, 15, 13, 11, 11,
+];
+
+/// Boolean function of each 16 steps. Right line uses them in reverse order.
+#[inline(always)]
+fn f(round: usize, x: Word, y: Word, z: Word) -> Word {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+fn compress(hash_val: &mut HashValue, x: &MsgBlock) {
+    let [mut al, mut bl, mut cl, mut dl, mut el] = *hash_val;
+    let [mut ar, mut br, mut cr, mut dr, mut er] = *hash_val;
+
+    for j in 0..80 {
+        let round = j / 16;
+
+        let t = al
+            .wrapping_add(f(round, bl, cl, dl))
+            .wrapping_add(x[R_LEFT[j]])
+            .wrapping_add(K_LEFT[round])
+            .rotate_left(S_LEFT[j])
+            .wrapping_add(el);
+        al = el;
+        el = dl;
+        dl = cl.rotate_left(10);
+        cl = bl;
+        bl = t;
+
+        let t = ar
+            .wrapping_add(f(4 - round, br, cr, dr))
+            .wrapping_add(x[R_RIGHT[j]])
+            .wrapping_add(K_RIGHT[round])
+            .rotate_left(S_RIGHT[j])
+            .wrapping_add(er);
+        ar = er;
+        er = dr;
+        dr = cr.rotate_left(10);
+        cr = br;
+        br = t;
+    }
+
+    let t = hash_val[1].wrapping_add(cl).wrapping_add(dr);
+    hash_val[1] = hash_val[2].wrapping_add(dl).wrapping_add(er);
+    hash_val[2] = hash_val[3].wrapping_add(el).wrapping_add(ar);
+    hash_val[3] = hash_val[4].wrapping_add(al).wrapping_add(br);
+    hash_val[4] = hash_val[0].wrapping_add(bl).wrapping_add(cr);
+    hash_val[0] = t;
+}
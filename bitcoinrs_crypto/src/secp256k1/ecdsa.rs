@@ -0,0 +1,274 @@
+use hmac::HmacSha256;
+
+use super::field::FieldElement;
+use super::group::Point;
+use super::key::{PublicKey, SecretKey};
+use super::scalar::Scalar;
+use super::Secp256k1Error;
+
+/// `p - n`. An x coordinate is greater than the group order only if `r` is less than this.
+const P_MINUS_N: [u8; 32] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x01, 0x45, 0x51, 0x23, 0x19, 0x50, 0xb7, 0x5f, 0xc4, 0x40,
+    0x2d, 0xa1, 0x72, 0x2f, 0xc9, 0xba, 0xee,
+];
+
+/// Group order `n` as a field element.
+const N_BYTES: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe, 0xba, 0xae,
+    0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// ECDSA signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    r: Scalar,
+    s: Scalar,
+}
+
+/// Which of up to four points `r` came from. With it, the public key can be recovered
+/// from a signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecoveryId(u8);
+
+impl Signature {
+    /// Returns an error if `r` or `s` is zero.
+    pub fn new(r: Scalar, s: Scalar) -> Result<Signature, Secp256k1Error> {
+        if r.is_zero() || s.is_zero() {
+            return Err(Secp256k1Error::InvalidSignature);
+        }
+        Ok(Signature { r, s })
+    }
+
+    pub fn r(&self) -> &Scalar {
+        &self.r
+    }
+
+    pub fn s(&self) -> &Scalar {
+        &self.s
+    }
+
+    /// Parse 64 bytes of `r` and `s` in big endian.
+    pub fn from_compact(bytes: &[u8; 64]) -> Result<Signature, Secp256k1Error> {
+        let scalar = |bytes: &[u8]| {
+            let mut array = [0; 32];
+            array.copy_from_slice(bytes);
+            Scalar::from_bytes(&array).ok_or(Secp256k1Error::InvalidSignature)
+        };
+        Signature::new(scalar(&bytes[..32])?, scalar(&bytes[32..])?)
+    }
+
+    pub fn to_compact(&self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&self.r.to_bytes());
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+
+    /// Parse strict DER encoding as required by BIP66, without a sighash type.
+    pub fn from_der(bytes: &[u8]) -> Result<Signature, Secp256k1Error> {
+        if bytes.len() < 2 || bytes[0] != 0x30 || bytes[1] as usize != bytes.len() - 2 {
+            return Err(Secp256k1Error::InvalidSignature);
+        }
+        let (r, rest) = parse_der_integer(&bytes[2..])?;
+        let (s, rest) = parse_der_integer(rest)?;
+        if !rest.is_empty() {
+            return Err(Secp256k1Error::InvalidSignature);
+        }
+        Signature::new(r, s)
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut bytes = vec![0x30, 0];
+        write_der_integer(&mut bytes, &self.r);
+        write_der_integer(&mut bytes, &self.s);
+        bytes[1] = (bytes.len() - 2) as u8;
+        bytes
+    }
+
+    /// Whether `s` is at most a half of the group order, as required by standardness (BIP146).
+    pub fn is_low_s(&self) -> bool {
+        !self.s.is_high()
+    }
+
+    /// Equivalent signature with low `s`. Both `s` and `-s` are valid for the same message.
+    pub fn normalize_s(&self) -> Signature {
+        Signature {
+            r: self.r,
+            s: Scalar::select(&self.s, &-self.s, self.s.is_high()),
+        }
+    }
+}
+
+/// Parse a DER integer and return it with rest of the bytes.
+fn parse_der_integer(bytes: &[u8]) -> Result<(Scalar, &[u8]), Secp256k1Error> {
+    let invalid = Err(Secp256k1Error::InvalidSignature);
+    if bytes.len() < 2 || bytes[0] != 0x02 {
+        return invalid;
+    }
+    let len = bytes[1] as usize;
+    if len == 0 || bytes.len() < 2 + len {
+        return invalid;
+    }
+    let (int, rest) = bytes[2..].split_at(len);
+
+    // Negative, or not in the shortest form.
+    if int[0] & 0x80 != 0 || (len > 1 && int[0] == 0 && int[1] & 0x80 == 0) {
+        return invalid;
+    }
+    let int = if int[0] == 0 { &int[1..] } else { int };
+    if int.len() > 32 {
+        return invalid;
+    }
+    let mut array = [0; 32];
+    array[32 - int.len()..].copy_from_slice(int);
+    match Scalar::from_bytes(&array) {
+        Some(scalar) => Ok((scalar, rest)),
+        None => invalid,
+    }
+}
+
+fn write_der_integer(out: &mut Vec<u8>, scalar: &Scalar) {
+    let bytes = scalar.to_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(31);
+    let pad = bytes[start] & 0x80 != 0;
+    out.push(0x02);
+    out.push((32 - start + pad as usize) as u8);
+    if pad {
+        out.push(0);
+    }
+    out.extend_from_slice(&bytes[start..]);
+}
+
+impl RecoveryId {
+    /// Returns an error unless `id` is 0, 1, 2 or 3.
+    pub fn from_u8(id: u8) -> Result<RecoveryId, Secp256k1Error> {
+        if id < 4 {
+            Ok(RecoveryId(id))
+        } else {
+            Err(Secp256k1Error::InvalidRecoveryId)
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        self.0
+    }
+}
+
+impl SecretKey {
+    /// Sign 32 bytes message hash with a RFC6979 deterministic nonce. Its `s` is always low.
+    pub fn sign_ecdsa(&self, msg: &[u8; 32]) -> Signature {
+        self.sign_ecdsa_recoverable(msg).0
+    }
+
+    pub fn sign_ecdsa_recoverable(&self, msg: &[u8; 32]) -> (Signature, RecoveryId) {
+        let d = *self.as_scalar();
+        let z = Scalar::from_bytes_reduced(msg);
+        let mut nonces = Rfc6979::new(&self.to_bytes(), &z.to_bytes());
+
+        loop {
+            let k = nonces.next();
+            let (x, y) = (Point::GENERATOR * k).to_affine().expect("k is non-zero");
+            let r = Scalar::from_bytes_reduced(&x.to_bytes());
+            let s = k.invert() * (z + r * d);
+            if r.is_zero() || s.is_zero() {
+                continue;
+            }
+
+            let mut recid = y.is_odd() as u8;
+            if Scalar::from_bytes(&x.to_bytes()).is_none() {
+                recid |= 2;
+            }
+            // Negating `s` corresponds to negating the point, which flips parity of y.
+            if s.is_high() {
+                recid ^= 1;
+            }
+            let sig = Signature { r, s }.normalize_s();
+            return (sig, RecoveryId(recid));
+        }
+    }
+}
+
+impl PublicKey {
+    /// Verify a signature of 32 bytes message hash. Both low and high `s` are accepted.
+    pub fn verify_ecdsa(&self, msg: &[u8; 32], sig: &Signature) -> bool {
+        let z = Scalar::from_bytes_reduced(msg);
+        let w = sig.s.invert();
+        let point = Point::GENERATOR * (z * w) + *self.as_point() * (sig.r * w);
+        match point.to_affine() {
+            Some((x, _)) => Scalar::from_bytes_reduced(&x.to_bytes()) == sig.r,
+            None => false,
+        }
+    }
+
+    /// Recover the public key which made `sig` of 32 bytes message hash.
+    pub fn recover_ecdsa(msg: &[u8; 32], sig: &Signature, recid: RecoveryId) -> Result<PublicKey, Secp256k1Error> {
+        let r_bytes = sig.r.to_bytes();
+        let x = if recid.0 & 2 == 0 {
+            FieldElement::from_bytes(&r_bytes)
+        } else if r_bytes < P_MINUS_N {
+            FieldElement::from_bytes(&r_bytes)
+                .and_then(|r| FieldElement::from_bytes(&N_BYTES).map(|n| r + n))
+        } else {
+            None
+        };
+        let point = x
+            .and_then(|x| Point::lift_x(x, recid.0 & 1 == 1))
+            .ok_or(Secp256k1Error::InvalidSignature)?;
+
+        let z = Scalar::from_bytes_reduced(msg);
+        let r_inv = sig.r.invert();
+        PublicKey::from_point(point * (sig.s * r_inv) - Point::GENERATOR * (z * r_inv))
+    }
+}
+
+/// Deterministic nonce generation by RFC6979 with HMAC-SHA256.
+struct Rfc6979 {
+    k: [u8; 32],
+    v: [u8; 32],
+}
+
+impl Rfc6979 {
+    fn new(secret: &[u8; 32], msg: &[u8; 32]) -> Rfc6979 {
+        let mut gen = Rfc6979 {
+            k: [0x00; 32],
+            v: [0x01; 32],
+        };
+        for &sep in [0x00, 0x01].iter() {
+            let mut hmac = HmacSha256::new(&gen.k);
+            hmac.update(&gen.v);
+            hmac.update(&[sep]);
+            hmac.update(secret);
+            hmac.update(msg);
+            gen.k = hmac.finalize();
+            gen.v = gen.hmac_v();
+        }
+        gen
+    }
+
+    fn next(&mut self) -> Scalar {
+        loop {
+            self.v = self.hmac_v();
+            let k = Scalar::from_bytes(&self.v);
+            // Prepare for the next call in case this nonce is rejected.
+            self.retry();
+            match k {
+                Some(k) if !k.is_zero() => return k,
+                _ => continue,
+            }
+        }
+    }
+
+    fn retry(&mut self) {
+        let mut hmac = HmacSha256::new(&self.k);
+        hmac.update(&self.v);
+        hmac.update(&[0x00]);
+        self.k = hmac.finalize();
+        self.v = self.hmac_v();
+    }
+
+    fn hmac_v(&self) -> [u8; 32] {
+        let mut hmac = HmacSha256::new(&self.k);
+        hmac.update(&self.v);
+        hmac.finalize()
+    }
+}
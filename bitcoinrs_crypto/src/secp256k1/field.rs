@@ -0,0 +1,217 @@
+use std::fmt::{Debug, Error as FmtError, Formatter};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use bitcoinrs_bytes::decode::{Decodable, DecodeError, DecodeErrorKind, ReadBuffer};
+use bitcoinrs_bytes::encode::EncodableSized;
+
+use super::limbs::{self, Limbs};
+
+/// Field prime `p = 2^256 - 2^32 - 977`.
+const P: Limbs = [0xfffffffefffffc2f, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff];
+
+/// `2^256 - p`.
+const P_COMPLEMENT: [u64; 3] = [0x1000003d1, 0, 0];
+
+/// `p - 2`, exponent of inversion.
+const P_MINUS_2: Limbs = [0xfffffffefffffc2d, 0xffffffffffffffff, 0xffffffffffffffff, 0xffffffffffffffff];
+
+/// `(p + 1) / 4`, exponent of square root.
+const P_PLUS_1_DIV_4: Limbs = [0xffffffffbfffff0c, 0xffffffffffffffff, 0xffffffffffffffff, 0x3fffffffffffffff];
+
+/// Element of the field of integers modulo `p`, which coordinates of curve points belong to.
+///
+/// It is always fully reduced, so two equal elements have the same representation.
+/// It is encoded as 32 bytes big endian.
+#[derive(Clone, Copy)]
+pub struct FieldElement(Limbs);
+
+impl FieldElement {
+    pub const ZERO: FieldElement = FieldElement([0; 4]);
+    pub const ONE: FieldElement = FieldElement([1, 0, 0, 0]);
+
+    pub fn from_u64(n: u64) -> FieldElement {
+        FieldElement([n, 0, 0, 0])
+    }
+
+    pub(super) const fn from_limbs(limbs: Limbs) -> FieldElement {
+        FieldElement(limbs)
+    }
+
+    /// Parse 32 bytes big endian. Returns `None` if it is not less than `p`.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<FieldElement> {
+        let a = limbs::from_be_bytes(bytes);
+        if limbs::lt(&a, &P) {
+            Some(FieldElement(a))
+        } else {
+            None
+        }
+    }
+
+    /// 32 bytes big endian.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        limbs::to_be_bytes(&self.0)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        limbs::is_zero(&self.0)
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.0[0] & 1 == 1
+    }
+
+    pub fn square(&self) -> FieldElement {
+        *self * *self
+    }
+
+    /// Multiplicative inverse, or zero for zero.
+    pub fn invert(&self) -> FieldElement {
+        FieldElement(limbs::pow_mod(&self.0, &P_MINUS_2, mul))
+    }
+
+    /// Either of square roots. Returns `None` if it is not a quadratic residue.
+    pub fn sqrt(&self) -> Option<FieldElement> {
+        // Since p = 3 (mod 4), a^((p+1)/4) is a square root if any.
+        let root = FieldElement(limbs::pow_mod(&self.0, &P_PLUS_1_DIV_4, mul));
+        if root.square() == *self {
+            Some(root)
+        } else {
+            None
+        }
+    }
+
+    /// `a` if `flag` is false, `b` if `flag` is true, in constant time.
+    pub fn select(a: &FieldElement, b: &FieldElement, flag: bool) -> FieldElement {
+        FieldElement(limbs::select(&a.0, &b.0, limbs::mask(flag)))
+    }
+}
+
+fn mul(a: &Limbs, b: &Limbs) -> Limbs {
+    limbs::reduce_wide(&limbs::mul_wide(a, b), &P, &P_COMPLEMENT)
+}
+
+impl PartialEq for FieldElement {
+    /// Constant time comparison.
+    fn eq(&self, other: &FieldElement) -> bool {
+        let (diff, _) = limbs::sub(&self.0, &other.0);
+        limbs::is_zero(&diff)
+    }
+}
+
+impl Eq for FieldElement {}
+
+impl Add for FieldElement {
+    type Output = FieldElement;
+
+    fn add(self, rhs: FieldElement) -> FieldElement {
+        FieldElement(limbs::add_mod(&self.0, &rhs.0, &P))
+    }
+}
+
+impl Sub for FieldElement {
+    type Output = FieldElement;
+
+    fn sub(self, rhs: FieldElement) -> FieldElement {
+        FieldElement(limbs::sub_mod(&self.0, &rhs.0, &P))
+    }
+}
+
+impl Mul for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, rhs: FieldElement) -> FieldElement {
+        FieldElement(mul(&self.0, &rhs.0))
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = FieldElement;
+
+    fn neg(self) -> FieldElement {
+        FieldElement::ZERO - self
+    }
+}
+
+impl Debug for FieldElement {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "FieldElement({})", ::bitcoinrs_bytes::hex::encode(&self.to_bytes()))
+    }
+}
+
+impl EncodableSized for FieldElement {
+    const SIZE: usize = 32;
+    type Array = [u8; 32];
+
+    fn bytes(&self) -> [u8; 32] {
+        self.to_bytes()
+    }
+}
+
+impl Decodable for FieldElement {
+    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<FieldElement, DecodeError> {
+        let bytes = buf.read::<[u8; 32]>()?;
+        FieldElement::from_bytes(&bytes).ok_or_else(|| DecodeError::new(DecodeErrorKind::InvalidBytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinrs_bytes::decode::Decodable;
+    use bitcoinrs_bytes::encode::Encodable;
+    use bitcoinrs_bytes::hex::FromHexError;
+
+    fn fe(hex: &str) -> FieldElement {
+        FieldElement::from_hex(hex).unwrap()
+    }
+
+    // Coordinates of the generator.
+    const GX: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    const GY: &str = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+    #[test]
+    fn arithmetic() {
+        let (a, b) = (fe(GX), fe(GY));
+
+        assert_eq!(a + b, fe("c1f940f620808011b3455e91dc9813afffb3b123d4537cf2f63a51eb1208ec50"));
+        assert_eq!(a - b, fe("31838c07d338f746f7fb6699c076025e058448928748d4bfbdaab0cb1be742e0"));
+        assert_eq!(b - a, fe("ce7c73f82cc708b9080499663f89fda1fa7bb76d78b72b4042554f33e418b94f"));
+        assert_eq!(a * b, fe("fd3dc529c6eb60fb9d166034cf3c1a5a72324aa9dfd3428a56d7e1ce0179fd9b"));
+        assert_eq!(-a, fe("8641998106234453aa5f9d6a3178f4f8fd640324d231d726a60d7ea3e907e497"));
+        assert_eq!(a.invert(), fe("237afdf1d2938d86870aaeb8ad77626a67b8e794abfb076be61d003687ca9ef6"));
+        assert_eq!(a * a.invert(), FieldElement::ONE);
+        assert_eq!(FieldElement::ZERO.invert(), FieldElement::ZERO);
+        assert_eq!(-FieldElement::ZERO, FieldElement::ZERO);
+
+        // p - 1 wraps around.
+        let p_minus_1 = fe("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e");
+        assert_eq!(p_minus_1 + FieldElement::ONE, FieldElement::ZERO);
+        assert_eq!(p_minus_1 * p_minus_1, FieldElement::ONE);
+        assert_eq!(FieldElement::ZERO - FieldElement::ONE, p_minus_1);
+    }
+
+    #[test]
+    fn sqrt() {
+        // y^2 = x^3 + 7
+        let y2 = fe(GX).square() * fe(GX) + FieldElement::from_u64(7);
+        let y = y2.sqrt().unwrap();
+        assert!(y == fe(GY) || -y == fe(GY));
+
+        assert_eq!(FieldElement::from_u64(3).sqrt(), None);
+        assert_eq!(FieldElement::ZERO.sqrt(), Some(FieldElement::ZERO));
+    }
+
+    #[test]
+    fn encode_decode() {
+        let a = fe(GX);
+        assert_eq!(a.to_vec(), ::bitcoinrs_bytes::hex::decode(GX).unwrap());
+        assert!(!a.is_odd() && FieldElement::ONE.is_odd());
+
+        // p is out of range.
+        let p = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+        match FieldElement::from_hex(p) {
+            Err(FromHexError::Decode(e)) => assert_eq!(e.kind(), DecodeErrorKind::InvalidBytes),
+            res => panic!("unexpected result {:?}", res),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::field::FieldElement;
+use super::scalar::Scalar;
+
+/// Curve constant `b` of `y^2 = x^3 + b`.
+const B: u64 = 7;
+
+/// Point on the curve, including the point at infinity.
+///
+/// It is kept in homogeneous projective coordinates `(X : Y : Z)` for `(X/Z, Y/Z)`,
+/// and uses the complete addition formulas by Renes, Costello and Batina,
+/// so that no input needs a special case.
+#[derive(Clone, Copy, Debug)]
+pub struct Point {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+}
+
+impl Point {
+    pub const INFINITY: Point = Point {
+        x: FieldElement::ZERO,
+        y: FieldElement::ONE,
+        z: FieldElement::ZERO,
+    };
+
+    pub const GENERATOR: Point = Point {
+        x: FieldElement::from_limbs([0x59f2815b16f81798, 0x029bfcdb2dce28d9, 0x55a06295ce870b07, 0x79be667ef9dcbbac]),
+        y: FieldElement::from_limbs([0x9c47d08ffb10d4b8, 0xfd17b448a6855419, 0x5da4fbfc0e1108a8, 0x483ada7726a3c465]),
+        z: FieldElement::ONE,
+    };
+
+    /// Returns `None` if `(x, y)` is not on the curve.
+    pub fn from_affine(x: FieldElement, y: FieldElement) -> Option<Point> {
+        if y.square() == x.square() * x + FieldElement::from_u64(B) {
+            Some(Point {
+                x,
+                y,
+                z: FieldElement::ONE,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Point with given `x` whose `y` has given parity.
+    /// Returns `None` if no point has `x`.
+    pub fn lift_x(x: FieldElement, odd: bool) -> Option<Point> {
+        let y = (x.square() * x + FieldElement::from_u64(B)).sqrt()?;
+        let y = FieldElement::select(&y, &-y, y.is_odd() != odd);
+        Some(Point {
+            x,
+            y,
+            z: FieldElement::ONE,
+        })
+    }
+
+    /// Affine coordinates, or `None` for the point at infinity.
+    pub fn to_affine(&self) -> Option<(FieldElement, FieldElement)> {
+        if self.is_infinity() {
+            return None;
+        }
+        let z_inv = self.z.invert();
+        Some((self.x * z_inv, self.y * z_inv))
+    }
+
+    pub fn is_infinity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    pub fn double(&self) -> Point {
+        *self + *self
+    }
+
+    /// `a` if `flag` is false, `b` if `flag` is true, in constant time.
+    pub fn select(a: &Point, b: &Point, flag: bool) -> Point {
+        Point {
+            x: FieldElement::select(&a.x, &b.x, flag),
+            y: FieldElement::select(&a.y, &b.y, flag),
+            z: FieldElement::select(&a.z, &b.z, flag),
+        }
+    }
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Point) -> bool {
+        // Compare `X1/Z1` with `X2/Z2` without inversion.
+        self.x * other.z == other.x * self.z && self.y * other.z == other.y * self.z
+    }
+}
+
+impl Eq for Point {}
+
+impl Add for Point {
+    type Output = Point;
+
+    /// Algorithm 7 of "Complete addition formulas for prime order elliptic curves".
+    fn add(self, rhs: Point) -> Point {
+        let b3 = FieldElement::from_u64(3 * B);
+        let (x1, y1, z1) = (self.x, self.y, self.z);
+        let (x2, y2, z2) = (rhs.x, rhs.y, rhs.z);
+
+        let t0 = x1 * x2;
+        let t1 = y1 * y2;
+        let t2 = z1 * z2;
+        let t3 = (x1 + y1) * (x2 + y2) - (t0 + t1);
+        let t4 = (y1 + z1) * (y2 + z2) - (t1 + t2);
+        let y3 = (x1 + z1) * (x2 + z2) - (t0 + t2);
+        let t0 = t0 + t0 + t0;
+        let t2 = b3 * t2;
+        let z3 = t1 + t2;
+        let t1 = t1 - t2;
+        let y3 = b3 * y3;
+        let x3 = t3 * t1 - t4 * y3;
+        let y3 = t1 * z3 + y3 * t0;
+        let z3 = z3 * t4 + t0 * t3;
+
+        Point {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point {
+            x: self.x,
+            y: -self.y,
+            z: self.z,
+        }
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        self + -rhs
+    }
+}
+
+impl Mul<Scalar> for Point {
+    type Output = Point;
+
+    fn mul(self, k: Scalar) -> Point {
+        scalar_mul(&self, &k)
+    }
+}
+
+/// Constant time scalar multiplication; it always doubles and adds for each bit.
+fn scalar_mul(point: &Point, k: &Scalar) -> Point {
+    let mut r = Point::INFINITY;
+    for i in (0..256).rev() {
+        r = r.double();
+        let sum = r + *point;
+        r = Point::select(&r, &sum, k.bit(i));
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinrs_bytes::decode::Decodable;
+
+    fn fe(hex: &str) -> FieldElement {
+        FieldElement::from_hex(hex).unwrap()
+    }
+
+    fn affine(x: &str, y: &str) -> Point {
+        Point::from_affine(fe(x), fe(y)).unwrap()
+    }
+
+    #[test]
+    fn add_and_double() {
+        let g = Point::GENERATOR;
+        let g2 = affine(
+            "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+            "1ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a",
+        );
+        let g3 = affine(
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+            "388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672",
+        );
+
+        assert_eq!(g.double(), g2);
+        assert_eq!(g + g, g2);
+        assert_eq!(g2 + g, g3);
+        assert_eq!(g + g2, g3);
+        assert_eq!(g3 - g, g2);
+        assert_eq!(g + -g, Point::INFINITY);
+        assert_eq!(g + Point::INFINITY, g);
+        assert_eq!(Point::INFINITY.double(), Point::INFINITY);
+        assert!((g - g).is_infinity());
+
+        let (x, y) = g3.to_affine().unwrap();
+        assert_eq!(x, fe("f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9"));
+        assert_eq!(y, fe("388f7b0f632de8140fe337e62a37f3566500a99934c2231b6cb9fd7584b8e672"));
+        assert_eq!(Point::INFINITY.to_affine(), None);
+    }
+
+    #[test]
+    fn scalar_mul() {
+        let g = Point::GENERATOR;
+        assert_eq!(g * Scalar::ZERO, Point::INFINITY);
+        assert_eq!(g * Scalar::ONE, g);
+        assert_eq!(g * Scalar::from_u64(3), g + g + g);
+        assert_eq!(g * -Scalar::ONE, -g);
+
+        // (n - 1) * G + G is the point at infinity.
+        assert!((g * -Scalar::ONE + g).is_infinity());
+
+        let a = Scalar::from_u64(0x1234_5678_9abc_def0);
+        let b = Scalar::from_u64(0x0fed_cba9_8765_4321);
+        assert_eq!(g * a * b, g * (a * b));
+        assert_eq!(g * a + g * b, g * (a + b));
+    }
+
+    #[test]
+    fn lift_x() {
+        let x = fe("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+        assert_eq!(Point::lift_x(x, false), Some(Point::GENERATOR));
+        assert_eq!(Point::lift_x(x, true), Some(-Point::GENERATOR));
+
+        // x = 5 is not on the curve, since 5^3 + 7 is not a quadratic residue.
+        assert_eq!(Point::lift_x(FieldElement::from_u64(5), false), None);
+        assert_eq!(Point::from_affine(x, x), None);
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt::{Debug, Error as FmtError, Formatter};
+
+use bitcoinrs_bytes::hex;
+
+use super::field::FieldElement;
+use super::group::Point;
+use super::scalar::Scalar;
+use super::Secp256k1Error;
+
+/// Secret key; a non-zero scalar.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SecretKey(Scalar);
+
+impl SecretKey {
+    /// Parse 32 bytes big endian, which must be non-zero and less than the group order.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<SecretKey, Secp256k1Error> {
+        match Scalar::from_bytes(bytes) {
+            Some(ref d) if !d.is_zero() => Ok(SecretKey(*d)),
+            _ => Err(Secp256k1Error::InvalidSecretKey),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn as_scalar(&self) -> &Scalar {
+        &self.0
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(Point::GENERATOR * self.0)
+    }
+}
+
+impl Debug for SecretKey {
+    /// Does not show the key itself.
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "SecretKey(..)")
+    }
+}
+
+/// Public key; a point on the curve other than the point at infinity.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(Point);
+
+impl PublicKey {
+    /// Parse 33 bytes compressed or 65 bytes uncompressed SEC1 encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<PublicKey, Secp256k1Error> {
+        let field = |bytes: &[u8]| {
+            let mut array = [0; 32];
+            array.copy_from_slice(bytes);
+            FieldElement::from_bytes(&array).ok_or(Secp256k1Error::InvalidPublicKey)
+        };
+
+        let point = match (bytes.len(), bytes.first()) {
+            (33, Some(&prefix)) if prefix == 0x02 || prefix == 0x03 => {
+                Point::lift_x(field(&bytes[1..])?, prefix == 0x03)
+            }
+            (65, Some(&0x04)) => Point::from_affine(field(&bytes[1..33])?, field(&bytes[33..])?),
+            _ => None,
+        };
+        point.map(PublicKey).ok_or(Secp256k1Error::InvalidPublicKey)
+    }
+
+    /// Returns an error for the point at infinity.
+    pub fn from_point(point: Point) -> Result<PublicKey, Secp256k1Error> {
+        if point.is_infinity() {
+            Err(Secp256k1Error::InvalidPublicKey)
+        } else {
+            Ok(PublicKey(point))
+        }
+    }
+
+    pub fn as_point(&self) -> &Point {
+        &self.0
+    }
+
+    /// 33 bytes compressed SEC1 encoding.
+    pub fn serialize(&self) -> [u8; 33] {
+        let (x, y) = self.affine();
+        let mut bytes = [0; 33];
+        bytes[0] = if y.is_odd() { 0x03 } else { 0x02 };
+        bytes[1..].copy_from_slice(&x.to_bytes());
+        bytes
+    }
+
+    /// 65 bytes uncompressed SEC1 encoding.
+    pub fn serialize_uncompressed(&self) -> [u8; 65] {
+        let (x, y) = self.affine();
+        let mut bytes = [0; 65];
+        bytes[0] = 0x04;
+        bytes[1..33].copy_from_slice(&x.to_bytes());
+        bytes[33..].copy_from_slice(&y.to_bytes());
+        bytes
+    }
+
+    pub(super) fn affine(&self) -> (FieldElement, FieldElement) {
+        self.0.to_affine().expect("public key is never the point at infinity")
+    }
+}
+
+impl Debug for PublicKey {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "PublicKey({})", hex::encode(&self.serialize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_key_encoding() {
+        let mut one = [0; 32];
+        one[31] = 1;
+        let pubkey = SecretKey::from_bytes(&one).unwrap().public_key();
+        assert_eq!(*pubkey.as_point(), Point::GENERATOR);
+
+        let compressed = pubkey.serialize();
+        assert_eq!(
+            hex::encode(&compressed),
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+        let uncompressed = pubkey.serialize_uncompressed();
+        assert_eq!(
+            hex::encode(&uncompressed),
+            "0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+             483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"
+        );
+        assert_eq!(PublicKey::from_bytes(&compressed), Ok(pubkey));
+        assert_eq!(PublicKey::from_bytes(&uncompressed), Ok(pubkey));
+
+        // Odd y.
+        let mut odd = compressed;
+        odd[0] = 0x03;
+        assert_eq!(PublicKey::from_bytes(&odd).map(|k| *k.as_point()), Ok(-Point::GENERATOR));
+
+        let invalid = Err(Secp256k1Error::InvalidPublicKey);
+        assert_eq!(PublicKey::from_bytes(&compressed[..32]), invalid);
+        assert_eq!(PublicKey::from_bytes(&[]), invalid);
+        let mut hybrid = uncompressed;
+        hybrid[0] = 0x06;
+        assert_eq!(PublicKey::from_bytes(&hybrid), invalid);
+        let mut off_curve = uncompressed;
+        off_curve[64] ^= 1;
+        assert_eq!(PublicKey::from_bytes(&off_curve), invalid);
+        assert_eq!(PublicKey::from_point(Point::INFINITY), invalid);
+    }
+
+    #[test]
+    fn secret_key_range() {
+        assert_eq!(SecretKey::from_bytes(&[0; 32]), Err(Secp256k1Error::InvalidSecretKey));
+        assert_eq!(SecretKey::from_bytes(&[0xff; 32]), Err(Secp256k1Error::InvalidSecretKey));
+        assert_eq!(format!("{:?}", SecretKey::from_bytes(&[1; 32]).unwrap()), "SecretKey(..)");
+    }
+}
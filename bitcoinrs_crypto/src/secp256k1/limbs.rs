@@ -0,0 +1,158 @@
+//! Constant-time arithmetic on 256 bits integers in little endian 64 bits limbs,
+//! shared by field elements and scalars.
+
+pub type Limbs = [u64; 4];
+
+/// All ones if `flag` is true, otherwise zero.
+#[inline]
+pub fn mask(flag: bool) -> u64 {
+    (flag as u64).wrapping_neg()
+}
+
+/// `a` if `mask` is zero, `b` if `mask` is all ones.
+#[inline]
+pub fn select(a: &Limbs, b: &Limbs, mask: u64) -> Limbs {
+    let mut r = [0; 4];
+    for i in 0..4 {
+        r[i] = a[i] ^ (mask & (a[i] ^ b[i]));
+    }
+    r
+}
+
+#[inline]
+pub fn is_zero(a: &Limbs) -> bool {
+    (a[0] | a[1] | a[2] | a[3]) == 0
+}
+
+/// `a + b` and carry.
+#[inline]
+pub fn add(a: &Limbs, b: &Limbs) -> (Limbs, u64) {
+    let mut r = [0; 4];
+    let mut carry = 0;
+    for i in 0..4 {
+        let t = u128::from(a[i]) + u128::from(b[i]) + carry;
+        r[i] = t as u64;
+        carry = t >> 64;
+    }
+    (r, carry as u64)
+}
+
+/// `a - b` and borrow.
+#[inline]
+pub fn sub(a: &Limbs, b: &Limbs) -> (Limbs, u64) {
+    let mut r = [0; 4];
+    let mut borrow = 0;
+    for i in 0..4 {
+        let t = u128::from(a[i]).wrapping_sub(u128::from(b[i]) + borrow);
+        r[i] = t as u64;
+        borrow = t >> 127;
+    }
+    (r, borrow as u64)
+}
+
+/// `a + b mod m` where both `a` and `b` are less than `m`.
+#[inline]
+pub fn add_mod(a: &Limbs, b: &Limbs, m: &Limbs) -> Limbs {
+    let (sum, carry) = add(a, b);
+    let (reduced, borrow) = sub(&sum, m);
+    // Keep `sum` only if it neither overflowed nor reached `m`.
+    select(&reduced, &sum, mask(carry == 0) & mask(borrow == 1))
+}
+
+/// `a - b mod m` where both `a` and `b` are less than `m`.
+#[inline]
+pub fn sub_mod(a: &Limbs, b: &Limbs, m: &Limbs) -> Limbs {
+    let (diff, borrow) = sub(a, b);
+    let (wrapped, _) = add(&diff, m);
+    select(&diff, &wrapped, mask(borrow == 1))
+}
+
+/// Full 512 bits product.
+#[inline]
+pub fn mul_wide(a: &Limbs, b: &Limbs) -> [u64; 8] {
+    let mut r = [0; 8];
+    for i in 0..4 {
+        let mut carry = 0;
+        for j in 0..4 {
+            let t = u128::from(a[i]) * u128::from(b[j]) + u128::from(r[i + j]) + carry;
+            r[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        r[i + 4] = carry as u64;
+    }
+    r
+}
+
+/// `x mod m`, where `c = 2^256 - m` is less than 2^130.
+#[inline]
+pub fn reduce_wide(x: &[u64; 8], m: &Limbs, c: &[u64; 3]) -> Limbs {
+    // Since 2^256 = c (mod m), `hi * 2^256 + lo` is replaced by `hi * c + lo`.
+    // Starting from 512 bits, it becomes less than 2^387, 2^261, 2^256 + 2^135,
+    // and finally less than 2^256 after four folds.
+    let mut acc = *x;
+    for _ in 0..4 {
+        acc = fold(&acc, c);
+    }
+    let lo = [acc[0], acc[1], acc[2], acc[3]];
+
+    // Now `lo < 2^256 < 2 * m`.
+    let (reduced, borrow) = sub(&lo, m);
+    select(&reduced, &lo, mask(borrow == 1))
+}
+
+/// `lo + hi * c`, where `lo` and `hi` are lower and upper halves of `x`.
+#[inline]
+fn fold(x: &[u64; 8], c: &[u64; 3]) -> [u64; 8] {
+    let mut r = [x[0], x[1], x[2], x[3], 0, 0, 0, 0];
+    for i in 0..4 {
+        let mut carry = 0;
+        for j in 0..3 {
+            let t = u128::from(x[4 + i]) * u128::from(c[j]) + u128::from(r[i + j]) + carry;
+            r[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        for limb in r.iter_mut().skip(i + 3) {
+            let t = u128::from(*limb) + carry;
+            *limb = t as u64;
+            carry = t >> 64;
+        }
+    }
+    r
+}
+
+/// `a^e mod m` by a fixed sequence of squarings and multiplications,
+/// since `e` is always a public constant.
+pub fn pow_mod<F: Fn(&Limbs, &Limbs) -> Limbs>(a: &Limbs, e: &Limbs, mul: F) -> Limbs {
+    let mut r = [1, 0, 0, 0];
+    for i in (0..256).rev() {
+        r = mul(&r, &r);
+        if (e[i / 64] >> (i % 64)) & 1 == 1 {
+            r = mul(&r, a);
+        }
+    }
+    r
+}
+
+pub fn from_be_bytes(bytes: &[u8; 32]) -> Limbs {
+    let mut r = [0; 4];
+    for (i, limb) in r.iter_mut().enumerate() {
+        let mut word = [0; 8];
+        word.copy_from_slice(&bytes[24 - i * 8..32 - i * 8]);
+        *limb = u64::from_be_bytes(word);
+    }
+    r
+}
+
+pub fn to_be_bytes(a: &Limbs) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    for (i, limb) in a.iter().enumerate() {
+        bytes[24 - i * 8..32 - i * 8].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+/// `a < m`.
+#[inline]
+pub fn lt(a: &Limbs, m: &Limbs) -> bool {
+    sub(a, m).1 == 1
+}
@@ -0,0 +1,46 @@
+//! secp256k1 elliptic curve.
+//!
+//! Arithmetic on secret values runs in constant time; it has no branches nor memory accesses
+//! which depend on them.
+
+use std::error::Error;
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+mod limbs;
+mod field;
+mod scalar;
+mod group;
+mod key;
+mod ecdsa;
+mod schnorr;
+
+pub use self::field::FieldElement;
+pub use self::scalar::Scalar;
+pub use self::group::Point;
+pub use self::key::{PublicKey, SecretKey};
+pub use self::ecdsa::{RecoveryId, Signature};
+pub use self::schnorr::{SchnorrSignature, XOnlyPublicKey};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Secp256k1Error {
+    /// Secret key is zero or not less than the group order.
+    InvalidSecretKey,
+    /// Public key has invalid length or prefix, or is not on the curve.
+    InvalidPublicKey,
+    /// Signature is malformed or its values are out of range.
+    InvalidSignature,
+    InvalidRecoveryId,
+}
+
+impl Display for Secp256k1Error {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match *self {
+            Secp256k1Error::InvalidSecretKey => write!(f, "invalid secret key"),
+            Secp256k1Error::InvalidPublicKey => write!(f, "invalid public key"),
+            Secp256k1Error::InvalidSignature => write!(f, "invalid signature"),
+            Secp256k1Error::InvalidRecoveryId => write!(f, "invalid recovery id"),
+        }
+    }
+}
+
+impl Error for Secp256k1Error {}
@@ -0,0 +1,205 @@
+use std::fmt::{Debug, Error as FmtError, Formatter};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use bitcoinrs_bytes::decode::{Decodable, DecodeError, DecodeErrorKind, ReadBuffer};
+use bitcoinrs_bytes::encode::EncodableSized;
+
+use super::limbs::{self, Limbs};
+
+/// Group order `n`.
+const N: Limbs = [0xbfd25e8cd0364141, 0xbaaedce6af48a03b, 0xfffffffffffffffe, 0xffffffffffffffff];
+
+/// `2^256 - n`.
+const N_COMPLEMENT: [u64; 3] = [0x402da1732fc9bebf, 0x4551231950b75fc4, 0x1];
+
+/// `n - 2`, exponent of inversion.
+const N_MINUS_2: Limbs = [0xbfd25e8cd036413f, 0xbaaedce6af48a03b, 0xfffffffffffffffe, 0xffffffffffffffff];
+
+/// `n / 2`, the largest scalar which is not high.
+const N_HALF: Limbs = [0xdfe92f46681b20a0, 0x5d576e7357a4501d, 0xffffffffffffffff, 0x7fffffffffffffff];
+
+/// Integer modulo the group order `n`, such as secret keys and nonces.
+///
+/// It is always fully reduced, and encoded as 32 bytes big endian.
+#[derive(Clone, Copy)]
+pub struct Scalar(Limbs);
+
+impl Scalar {
+    pub const ZERO: Scalar = Scalar([0; 4]);
+    pub const ONE: Scalar = Scalar([1, 0, 0, 0]);
+
+    pub fn from_u64(n: u64) -> Scalar {
+        Scalar([n, 0, 0, 0])
+    }
+
+    /// Parse 32 bytes big endian. Returns `None` if it is not less than `n`.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<Scalar> {
+        let a = limbs::from_be_bytes(bytes);
+        if limbs::lt(&a, &N) {
+            Some(Scalar(a))
+        } else {
+            None
+        }
+    }
+
+    /// Parse 32 bytes big endian modulo `n`, e.g. a message hash.
+    pub fn from_bytes_reduced(bytes: &[u8; 32]) -> Scalar {
+        let a = limbs::from_be_bytes(bytes);
+        // `a < 2^256 < 2 * n`, so a single subtraction is enough.
+        let (reduced, borrow) = limbs::sub(&a, &N);
+        Scalar(limbs::select(&reduced, &a, limbs::mask(borrow == 1)))
+    }
+
+    /// 32 bytes big endian.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        limbs::to_be_bytes(&self.0)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        limbs::is_zero(&self.0)
+    }
+
+    /// Whether it is greater than `n / 2`. Signatures with high `s` are not standard.
+    pub fn is_high(&self) -> bool {
+        limbs::lt(&N_HALF, &self.0)
+    }
+
+    /// Multiplicative inverse, or zero for zero.
+    pub fn invert(&self) -> Scalar {
+        Scalar(limbs::pow_mod(&self.0, &N_MINUS_2, mul))
+    }
+
+    /// Bit at `index`, where index 0 is the least significant bit.
+    pub fn bit(&self, index: usize) -> bool {
+        (self.0[index / 64] >> (index % 64)) & 1 == 1
+    }
+
+    /// `a` if `flag` is false, `b` if `flag` is true, in constant time.
+    pub fn select(a: &Scalar, b: &Scalar, flag: bool) -> Scalar {
+        Scalar(limbs::select(&a.0, &b.0, limbs::mask(flag)))
+    }
+}
+
+fn mul(a: &Limbs, b: &Limbs) -> Limbs {
+    limbs::reduce_wide(&limbs::mul_wide(a, b), &N, &N_COMPLEMENT)
+}
+
+impl PartialEq for Scalar {
+    /// Constant time comparison.
+    fn eq(&self, other: &Scalar) -> bool {
+        let (diff, _) = limbs::sub(&self.0, &other.0);
+        limbs::is_zero(&diff)
+    }
+}
+
+impl Eq for Scalar {}
+
+impl Add for Scalar {
+    type Output = Scalar;
+
+    fn add(self, rhs: Scalar) -> Scalar {
+        Scalar(limbs::add_mod(&self.0, &rhs.0, &N))
+    }
+}
+
+impl Sub for Scalar {
+    type Output = Scalar;
+
+    fn sub(self, rhs: Scalar) -> Scalar {
+        Scalar(limbs::sub_mod(&self.0, &rhs.0, &N))
+    }
+}
+
+impl Mul for Scalar {
+    type Output = Scalar;
+
+    fn mul(self, rhs: Scalar) -> Scalar {
+        Scalar(mul(&self.0, &rhs.0))
+    }
+}
+
+impl Neg for Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Scalar {
+        Scalar::ZERO - self
+    }
+}
+
+impl Debug for Scalar {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "Scalar({})", ::bitcoinrs_bytes::hex::encode(&self.to_bytes()))
+    }
+}
+
+impl EncodableSized for Scalar {
+    const SIZE: usize = 32;
+    type Array = [u8; 32];
+
+    fn bytes(&self) -> [u8; 32] {
+        self.to_bytes()
+    }
+}
+
+impl Decodable for Scalar {
+    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<Scalar, DecodeError> {
+        let bytes = buf.read::<[u8; 32]>()?;
+        Scalar::from_bytes(&bytes).ok_or_else(|| DecodeError::new(DecodeErrorKind::InvalidBytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinrs_bytes::decode::Decodable;
+    use bitcoinrs_bytes::encode::Encodable;
+
+    fn s(hex: &str) -> Scalar {
+        Scalar::from_hex(hex).unwrap()
+    }
+
+    const A: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+    const B: &str = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+    const N_HEX: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+    #[test]
+    fn arithmetic() {
+        let (a, b) = (s(A), s(B));
+
+        assert_eq!(a + b, s("c1f940f620808011b3455e91dc9813afffb3b123d4537cf2f63a51eb1208ec50"));
+        assert_eq!(a - b, s("31838c07d338f746f7fb6699c076025e058448928748d4bfbdaab0cb1be742e0"));
+        assert_eq!(b - a, s("ce7c73f82cc708b9080499663f89fda0b52a945427ffcb7c0227adc1b44efe61"));
+        assert_eq!(a * b, s("805714a252d0c0b58910907e85b5b801fff610a36bdf46847a4bf5d9ae2d10ed"));
+        assert_eq!(-a, s("8641998106234453aa5f9d6a3178f4f7b812e00b817a776265dfdd31b93e29a9"));
+        assert_eq!(a.invert(), s("1dd887b3eaf153260a95e8b9fd31f60ac115d26ccbe1f572c0b8d7a6dec520fe"));
+        assert_eq!(a * a.invert(), Scalar::ONE);
+
+        let n_minus_1 = s("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140");
+        assert_eq!(n_minus_1 + Scalar::ONE, Scalar::ZERO);
+        assert_eq!(n_minus_1 * n_minus_1, Scalar::ONE);
+        assert_eq!(-Scalar::ONE, n_minus_1);
+    }
+
+    #[test]
+    fn reduce_and_range() {
+        let mut n = [0; 32];
+        ::bitcoinrs_bytes::hex::decode_to_slice(N_HEX, &mut n).unwrap();
+        assert_eq!(Scalar::from_bytes(&n), None);
+        assert_eq!(Scalar::from_bytes_reduced(&n), Scalar::ZERO);
+        assert_eq!(
+            Scalar::from_bytes_reduced(&[0xff; 32]),
+            s("000000000000000000000000000000014551231950b75fc4402da1732fc9bebe")
+        );
+        assert!(Scalar::from_hex(N_HEX).is_err());
+        assert_eq!(s(A).to_vec(), ::bitcoinrs_bytes::hex::decode(A).unwrap());
+    }
+
+    #[test]
+    fn high() {
+        let half = s("7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0");
+        assert!(!half.is_high());
+        assert!((half + Scalar::ONE).is_high());
+        assert!(!Scalar::ZERO.is_high());
+        assert!((-Scalar::ONE).is_high());
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt::{Debug, Error as FmtError, Formatter};
+
+use bitcoinrs_bytes::hex;
+
+use hash::{Bip340AuxHash, Bip340ChallengeHash, Bip340NonceHash};
+
+use super::field::FieldElement;
+use super::group::Point;
+use super::key::{PublicKey, SecretKey};
+use super::scalar::Scalar;
+use super::Secp256k1Error;
+
+/// BIP340 public key; only x coordinate of a point whose y is even.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct XOnlyPublicKey(Point);
+
+/// BIP340 signature; x coordinate of `R` and `s`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SchnorrSignature {
+    r: FieldElement,
+    s: Scalar,
+}
+
+impl XOnlyPublicKey {
+    /// Returns an error if `bytes` is not an x coordinate on the curve.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<XOnlyPublicKey, Secp256k1Error> {
+        FieldElement::from_bytes(bytes)
+            .and_then(|x| Point::lift_x(x, false))
+            .map(XOnlyPublicKey)
+            .ok_or(Secp256k1Error::InvalidPublicKey)
+    }
+
+    pub fn serialize(&self) -> [u8; 32] {
+        self.affine_x().to_bytes()
+    }
+
+    /// Point with even y.
+    pub fn as_point(&self) -> &Point {
+        &self.0
+    }
+
+    /// Verify a BIP340 signature of `msg`.
+    pub fn verify_schnorr(&self, msg: &[u8], sig: &SchnorrSignature) -> bool {
+        let e = challenge(&sig.r, self, msg);
+        let point = Point::GENERATOR * sig.s - self.0 * e;
+        match point.to_affine() {
+            Some((x, y)) => !y.is_odd() && x == sig.r,
+            None => false,
+        }
+    }
+
+    fn affine_x(&self) -> FieldElement {
+        self.0.to_affine().expect("public key is never the point at infinity").0
+    }
+}
+
+impl Debug for XOnlyPublicKey {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "XOnlyPublicKey({})", hex::encode(&self.serialize()))
+    }
+}
+
+impl SchnorrSignature {
+    /// Returns an error if `r` is not less than the field size or `s` is not less than the group order.
+    pub fn from_bytes(bytes: &[u8; 64]) -> Result<SchnorrSignature, Secp256k1Error> {
+        let mut r = [0; 32];
+        let mut s = [0; 32];
+        r.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..]);
+        match (FieldElement::from_bytes(&r), Scalar::from_bytes(&s)) {
+            (Some(r), Some(s)) => Ok(SchnorrSignature { r, s }),
+            _ => Err(Secp256k1Error::InvalidSignature),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..32].copy_from_slice(&self.r.to_bytes());
+        bytes[32..].copy_from_slice(&self.s.to_bytes());
+        bytes
+    }
+}
+
+impl PublicKey {
+    /// X-only public key and whether y of the original point is odd.
+    pub fn x_only_public_key(&self) -> (XOnlyPublicKey, bool) {
+        let (_, y) = self.affine();
+        let odd = y.is_odd();
+        let point = Point::select(self.as_point(), &-*self.as_point(), odd);
+        (XOnlyPublicKey(point), odd)
+    }
+}
+
+impl SecretKey {
+    /// Sign `msg` by BIP340 with auxiliary random data, which should be fresh randomness
+    /// but signatures are still secure with a constant one.
+    pub fn sign_schnorr(&self, msg: &[u8], aux_rand: &[u8; 32]) -> SchnorrSignature {
+        let (pubkey, odd) = self.public_key().x_only_public_key();
+        let d = Scalar::select(self.as_scalar(), &-*self.as_scalar(), odd);
+
+        // Mask the secret key with hashed auxiliary data.
+        let aux = Bip340AuxHash::hash(aux_rand);
+        let mut t = d.to_bytes();
+        for (t, a) in t.iter_mut().zip(aux.as_bytes().iter()) {
+            *t ^= a;
+        }
+
+        let mut engine = Bip340NonceHash::engine();
+        engine.update(&t);
+        engine.update(&pubkey.serialize());
+        engine.update(msg);
+        let k = Scalar::from_bytes_reduced(Bip340NonceHash::from_engine(engine).as_bytes());
+        // Zero nonce happens only with negligible probability.
+        assert!(!k.is_zero(), "BIP340 nonce is zero");
+
+        let (r, ry) = (Point::GENERATOR * k).to_affine().expect("k is non-zero");
+        let k = Scalar::select(&k, &-k, ry.is_odd());
+        let e = challenge(&r, &pubkey, msg);
+        SchnorrSignature { r, s: k + e * d }
+    }
+}
+
+/// `e = int(hash_challenge(r || P || m)) mod n`.
+fn challenge(r: &FieldElement, pubkey: &XOnlyPublicKey, msg: &[u8]) -> Scalar {
+    let mut engine = Bip340ChallengeHash::engine();
+    engine.update(&r.to_bytes());
+    engine.update(&pubkey.serialize());
+    engine.update(msg);
+    Scalar::from_bytes_reduced(Bip340ChallengeHash::from_engine(engine).as_bytes())
+}
@@ -1,95 +1,173 @@
-use std::io::Cursor;
-
-use bitcoinrs_bytes::decode::ReadBuffer;
 use bitcoinrs_bytes::encode::WriteBuffer;
-use bitcoinrs_bytes::endian::{u32_b, u64_b};
+
+use hash::HashEngine;
 
 type Word = u32;
 type HashValue = [Word; 8];
-type MsgBlock = [Word; 16];
-type ExpandedMsgBlock = [Word; 64]; // aka message schedule.
-
-pub fn sha256(msg: &[u8]) -> [u8; 32] {
-    // Preprocessing
-    let bytes = get_padded_bytes(msg);
-    let msg_block_iter = MsgBlockIter::new(bytes.as_slice());
+type MsgBlock = [Word; 16]; // Also used as a rolling message schedule.
 
-    // Computation
-    let hash_val = compute_hash(msg_block_iter);
+const BLOCK_SIZE: usize = 512 / 8;
 
-    // Finalize
-    parse_into_result(hash_val)
+pub fn sha256(msg: &[u8]) -> [u8; 32] {
+    let mut engine = Sha256::new();
+    engine.update(msg);
+    engine.finalize()
 }
 
 /* ===================================== */
-/* Preprocessing */
+/* Engine */
 /* ===================================== */
 
-const BYTE_SIZE_PADD_BASE: usize = 512 / 8;
-const BYTE_SIZE_DATA_LEN: usize = 64 / 8;
+/// Streaming SHA-256 engine.
+///
+/// It also implements `WriteBuffer`, so any `Encodable` can be hashed without
+/// encoding it into an intermediate buffer.
+#[derive(Clone)]
+pub struct Sha256 {
+    hash_val: HashValue,
+    /// Bytes which do not fill a message block yet.
+    block: [u8; BLOCK_SIZE],
+    /// Total length of input in bytes.
+    length: u64,
+}
 
-fn get_padded_bytes(msg: &[u8]) -> Vec<u8> {
-    // Calc after padded size
-    let size_zero_padding = size_zero_padding(msg.len());
-    let padded_size = msg.len() + 1 + size_zero_padding + 8;
+impl Sha256 {
+    pub fn new() -> Sha256 {
+        Sha256 {
+            hash_val: INIT_HASH_VAL,
+            block: [0; BLOCK_SIZE],
+            length: 0,
+        }
+    }
 
-    // Prepare buffer
-    let mut bytes = Vec::with_capacity(padded_size);
-    bytes.write_bytes(msg);
-    bytes.write(0b_1000_0000_u8);
-    write_zeros(&mut bytes, size_zero_padding);
-    bytes.write(u64_b::new(msg.len() as u64 * 8)); // Length in bits.
+    /// Resume hashing from a midstate after `length` bytes of input.
+    ///
+    /// # Panic
+    /// when `length` is not a multiple of 64.
+    pub fn from_midstate(midstate: [u8; 32], length: u64) -> Sha256 {
+        assert!(length.is_multiple_of(BLOCK_SIZE as u64), "midstate length must be multiple of 64");
 
-    bytes
-}
+        let mut hash_val = [0; 8];
+        for (i, word) in hash_val.iter_mut().enumerate() {
+            *word = read_word(&midstate[i * 4..]);
+        }
+        Sha256 {
+            hash_val,
+            block: [0; BLOCK_SIZE],
+            length,
+        }
+    }
 
-fn size_zero_padding(l: usize) -> usize {
-    let resv_size = (l + 1 + BYTE_SIZE_DATA_LEN) % BYTE_SIZE_PADD_BASE;
-    BYTE_SIZE_PADD_BASE - resv_size
-}
+    /// Internal state after processing input so far, without padding.
+    /// Tagged hashes use it to skip hashing the common prefix.
+    ///
+    /// # Panic
+    /// when length of input so far is not a multiple of 64.
+    pub fn midstate(&self) -> [u8; 32] {
+        assert!(self.block_len() == 0, "midstate length must be multiple of 64");
+        parse_into_result(self.hash_val)
+    }
 
-fn write_zeros(vec: &mut Vec<u8>, zeros: usize) {
-    unsafe {
-        let p = vec.len();
-        vec.set_len(p + zeros);
+    /// Length of input so far in bytes.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        let block_len = self.block_len();
+        self.length += bytes.len() as u64;
+
+        // Fill pending block first.
+        if block_len > 0 {
+            let n = (BLOCK_SIZE - block_len).min(bytes.len());
+            self.block[block_len..block_len + n].copy_from_slice(&bytes[..n]);
+            bytes = &bytes[n..];
+            if block_len + n < BLOCK_SIZE {
+                return;
+            }
+            let block = self.block;
+            self.process(&block);
+        }
+
+        // Process whole blocks without copying.
+        let mut chunks = bytes.chunks_exact(BLOCK_SIZE);
+        for chunk in &mut chunks {
+            self.process(chunk);
+        }
+        let rest = chunks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+    }
+
+    pub fn finalize(mut self) -> [u8; 32] {
+        let bit_len = self.length * 8;
+
+        // Padding; a single 1 bit, zeros, and length in bits.
+        let block_len = self.block_len();
+        let zeros = (BLOCK_SIZE * 2 - block_len - 1 - 8) % BLOCK_SIZE;
+        let mut padding = [0; BLOCK_SIZE * 2];
+        padding[0] = 0b_1000_0000;
+        padding[1 + zeros..1 + zeros + 8].copy_from_slice(&bit_len.to_be_bytes());
+        self.update(&padding[..1 + zeros + 8]);
+        debug_assert!(self.block_len() == 0);
+
+        parse_into_result(self.hash_val)
+    }
+
+    fn block_len(&self) -> usize {
+        (self.length % BLOCK_SIZE as u64) as usize
+    }
+
+    fn process(&mut self, block: &[u8]) {
+        let mut msg_block: MsgBlock = [0; 16];
+        for (i, word) in msg_block.iter_mut().enumerate() {
+            *word = read_word(&block[i * 4..]);
+        }
+        compress(&mut self.hash_val, msg_block);
     }
 }
 
-struct MsgBlockIter<'a> {
-    msg: Cursor<&'a [u8]>,
+impl Default for Sha256 {
+    fn default() -> Sha256 {
+        Sha256::new()
+    }
 }
 
-impl<'a> MsgBlockIter<'a> {
-    pub fn new(msg: &'a [u8]) -> MsgBlockIter<'a> {
-        MsgBlockIter {
-            msg: Cursor::new(msg),
-        }
+impl WriteBuffer for Sha256 {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+
+    fn has_buffer(&self, _size: usize) -> bool {
+        true
     }
 }
 
-impl<'a> Iterator for MsgBlockIter<'a> {
-    type Item = MsgBlock;
+impl HashEngine for Sha256 {
+    type Output = [u8; 32];
 
-    fn next(&mut self) -> Option<MsgBlock> {
-        let mut msg_block = [0; 16];
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = 32;
 
-        for i in 0..16 {
-            msg_block[i] = self.msg.read::<u32_b>().ok()?.value();
-        }
-        Some(msg_block)
+    fn update(&mut self, bytes: &[u8]) {
+        Sha256::update(self, bytes);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        Sha256::finalize(self)
     }
 }
 
+/// Big endian word at the beginning of `bytes`.
+fn read_word(bytes: &[u8]) -> Word {
+    let mut word = [0; 4];
+    word.copy_from_slice(&bytes[..4]);
+    Word::from_be_bytes(word)
+}
+
 /* ===================================== */
 /* Computation */
 /* ===================================== */
 
-fn compute_hash(msg_blocks: MsgBlockIter) -> HashValue {
-    msg_blocks.fold(INIT_HASH_VAL, |hash, msg_block| {
-        compute_next_hash_val(msg_block, hash)
-    })
-}
-
 /// Sha-256 initial hash value.
 const INIT_HASH_VAL: HashValue = [
     0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19
@@ -106,96 +184,333 @@ const SHA256_CONST_WORDS: [Word; 64] = [
     0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
 ];
 
-fn compute_next_hash_val(msg_block: MsgBlock, prev_hash: HashValue) -> HashValue {
-    use self::word_ops::*;
+/// Operations on a word, or on words of several messages hashed at once.
+trait Lane: Copy {
+    fn splat(w: Word) -> Self;
+    fn add(self, other: Self) -> Self;
+    fn xor(self, other: Self) -> Self;
+    fn and(self, other: Self) -> Self;
+    fn not(self) -> Self;
+    fn rotr(self, n: u32) -> Self;
+    fn shr(self, n: u32) -> Self;
+}
+
+impl Lane for Word {
+    #[inline(always)]
+    fn splat(w: Word) -> Word {
+        w
+    }
+
+    #[inline(always)]
+    fn add(self, other: Word) -> Word {
+        self.wrapping_add(other)
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Word) -> Word {
+        self ^ other
+    }
+
+    #[inline(always)]
+    fn and(self, other: Word) -> Word {
+        self & other
+    }
+
+    #[inline(always)]
+    fn not(self) -> Word {
+        !self
+    }
+
+    #[inline(always)]
+    fn rotr(self, n: u32) -> Word {
+        self.rotate_right(n)
+    }
+
+    #[inline(always)]
+    fn shr(self, n: u32) -> Word {
+        self >> n
+    }
+}
+
+/// Number of messages `sha256d_64` hashes at once.
+const WAYS: usize = 4;
+
+/// Same word of `WAYS` messages.
+/// Used as is where `sse2::Sse2` is not available, hoping the compiler vectorizes it.
+type Ways = [Word; WAYS];
+
+macro_rules! ways {
+    ($f: expr) => {
+        [$f(0), $f(1), $f(2), $f(3)]
+    };
+}
+
+impl Lane for Ways {
+    #[inline(always)]
+    fn splat(w: Word) -> Ways {
+        [w; WAYS]
+    }
+
+    #[inline(always)]
+    fn add(self, other: Ways) -> Ways {
+        ways!(|i: usize| self[i].wrapping_add(other[i]))
+    }
+
+    #[inline(always)]
+    fn xor(self, other: Ways) -> Ways {
+        ways!(|i: usize| self[i] ^ other[i])
+    }
+
+    #[inline(always)]
+    fn and(self, other: Ways) -> Ways {
+        ways!(|i: usize| self[i] & other[i])
+    }
+
+    #[inline(always)]
+    fn not(self) -> Ways {
+        ways!(|i: usize| !self[i])
+    }
+
+    #[inline(always)]
+    fn rotr(self, n: u32) -> Ways {
+        ways!(|i: usize| self[i].rotate_right(n))
+    }
+
+    #[inline(always)]
+    fn shr(self, n: u32) -> Ways {
+        ways!(|i: usize| self[i] >> n)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    // Every x86_64 CPU has SSE2, so intrinsics below are always safe to call.
+    use std::arch::x86_64::*;
+
+    use super::{Lane, Ways, Word};
 
-    // Initialize working variables.
-    let (mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h) = (
-        prev_hash[0],
-        prev_hash[1],
-        prev_hash[2],
-        prev_hash[3],
-        prev_hash[4],
-        prev_hash[5],
-        prev_hash[6],
-        prev_hash[7],
-    );
-
-    // Prepare the expanded message block (aka message schedule).
-    let expanded_msg_block = get_expand_msg_block(msg_block);
-
-    // Compute working variables.
-    for t in 0..64 {
-        let t1 = h.wrapping_add(big_sigma_1(e))
-            .wrapping_add(choose(e, f, g))
-            .wrapping_add(SHA256_CONST_WORDS[t])
-            .wrapping_add(expanded_msg_block[t]);
-        let t2 = big_sigma_0(a).wrapping_add(majority(a, b, c));
-        h = g;
-        g = f;
-        f = e;
-        e = d.wrapping_add(t1);
-        d = c;
-        c = b;
-        b = a;
-        a = t1.wrapping_add(t2);
-    }
-
-    // Compute next hash value.
-    let mut new_hash = [0; 8];
-    new_hash[0] = a.wrapping_add(prev_hash[0]);
-    new_hash[1] = b.wrapping_add(prev_hash[1]);
-    new_hash[2] = c.wrapping_add(prev_hash[2]);
-    new_hash[3] = d.wrapping_add(prev_hash[3]);
-    new_hash[4] = e.wrapping_add(prev_hash[4]);
-    new_hash[5] = f.wrapping_add(prev_hash[5]);
-    new_hash[6] = g.wrapping_add(prev_hash[6]);
-    new_hash[7] = h.wrapping_add(prev_hash[7]);
-    new_hash
-}
-
-fn get_expand_msg_block(msg_block: MsgBlock) -> ExpandedMsgBlock {
-    let mut expanded = [0; 64];
-    expanded[..16].copy_from_slice(&msg_block);
+    /// `Ways` in a SSE2 register.
+    #[derive(Clone, Copy)]
+    pub struct Sse2(__m128i);
 
+    impl From<Ways> for Sse2 {
+        #[inline(always)]
+        fn from(ways: Ways) -> Sse2 {
+            Sse2(unsafe { _mm_loadu_si128(ways.as_ptr() as *const __m128i) })
+        }
+    }
+
+    impl From<Sse2> for Ways {
+        #[inline(always)]
+        fn from(x: Sse2) -> Ways {
+            let mut ways = [0; 4];
+            unsafe { _mm_storeu_si128(ways.as_mut_ptr() as *mut __m128i, x.0) };
+            ways
+        }
+    }
+
+    impl Lane for Sse2 {
+        #[inline(always)]
+        fn splat(w: Word) -> Sse2 {
+            Sse2(unsafe { _mm_set1_epi32(w as i32) })
+        }
+
+        #[inline(always)]
+        fn add(self, other: Sse2) -> Sse2 {
+            Sse2(unsafe { _mm_add_epi32(self.0, other.0) })
+        }
+
+        #[inline(always)]
+        fn xor(self, other: Sse2) -> Sse2 {
+            Sse2(unsafe { _mm_xor_si128(self.0, other.0) })
+        }
+
+        #[inline(always)]
+        fn and(self, other: Sse2) -> Sse2 {
+            Sse2(unsafe { _mm_and_si128(self.0, other.0) })
+        }
+
+        #[inline(always)]
+        fn not(self) -> Sse2 {
+            Sse2(unsafe { _mm_xor_si128(self.0, _mm_set1_epi32(-1)) })
+        }
+
+        #[inline(always)]
+        fn rotr(self, n: u32) -> Sse2 {
+            unsafe {
+                let right = _mm_srl_epi32(self.0, _mm_cvtsi32_si128(n as i32));
+                let left = _mm_sll_epi32(self.0, _mm_cvtsi32_si128(32 - n as i32));
+                Sse2(_mm_or_si128(right, left))
+            }
+        }
+
+        #[inline(always)]
+        fn shr(self, n: u32) -> Sse2 {
+            Sse2(unsafe { _mm_srl_epi32(self.0, _mm_cvtsi32_si128(n as i32)) })
+        }
+    }
+}
+
+/// Compression function. `w` is a message block and is overwritten by the message schedule.
+#[inline(always)]
+fn compress<L: Lane>(state: &mut [L; 8], mut w: [L; 16]) {
     use self::word_ops::*;
-    for t in 16..64 {
-        let w = small_sigma_1(expanded[t - 2])
-            .wrapping_add(expanded[t - 7])
-            .wrapping_add(small_sigma_0(expanded[t - 15]))
-            .wrapping_add(expanded[t - 16]);
-        expanded[t] = w;
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    // One round. Instead of shifting eight working variables, callers rotate arguments.
+    macro_rules! round {
+        ($a: ident, $b: ident, $c: ident, $d: ident,
+         $e: ident, $f: ident, $g: ident, $h: ident, $t: expr, $wt: expr) => {{
+            let t1 = $h
+                .add(big_sigma_1($e))
+                .add(choose($e, $f, $g))
+                .add(L::splat(SHA256_CONST_WORDS[$t]))
+                .add($wt);
+            let t2 = big_sigma_0($a).add(majority($a, $b, $c));
+            $d = $d.add(t1);
+            $h = t1.add(t2);
+        }};
+    }
+
+    // Message schedule is updated in place, keeping the last 16 words.
+    macro_rules! schedule {
+        ($t: expr) => {{
+            let t = $t;
+            let s = small_sigma_1(w[(t - 2) & 15])
+                .add(w[(t - 7) & 15])
+                .add(small_sigma_0(w[(t - 15) & 15]))
+                .add(w[t & 15]);
+            w[t & 15] = s;
+            s
+        }};
+    }
+
+    macro_rules! eight_rounds {
+        ($t: expr, $wt: ident) => {{
+            let t = $t;
+            round!(a, b, c, d, e, f, g, h, t, $wt!(t));
+            round!(h, a, b, c, d, e, f, g, t + 1, $wt!(t + 1));
+            round!(g, h, a, b, c, d, e, f, t + 2, $wt!(t + 2));
+            round!(f, g, h, a, b, c, d, e, t + 3, $wt!(t + 3));
+            round!(e, f, g, h, a, b, c, d, t + 4, $wt!(t + 4));
+            round!(d, e, f, g, h, a, b, c, t + 5, $wt!(t + 5));
+            round!(c, d, e, f, g, h, a, b, t + 6, $wt!(t + 6));
+            round!(b, c, d, e, f, g, h, a, t + 7, $wt!(t + 7));
+        }};
+    }
+
+    macro_rules! msg_word {
+        ($t: expr) => {
+            w[$t]
+        };
+    }
+
+    eight_rounds!(0, msg_word);
+    eight_rounds!(8, msg_word);
+    for t in (16..64).step_by(8) {
+        eight_rounds!(t, schedule);
+    }
+
+    let working = [a, b, c, d, e, f, g, h];
+    for (s, v) in state.iter_mut().zip(working.iter()) {
+        *s = s.add(*v);
     }
+}
 
-    expanded
+/// Double SHA-256 of a 64 bytes message; the second block and the second hash have
+/// fixed padding, so they are computed without going through `Sha256`.
+#[inline(always)]
+fn sha256d_64_lanes<L: Lane>(msg_block: [L; 16]) -> [L; 8] {
+    // First hash; the message block and a padding block.
+    let mut state = INIT_HASH_VAL.map(L::splat);
+    compress(&mut state, msg_block);
+    let mut padding = [L::splat(0); 16];
+    padding[0] = L::splat(0x8000_0000);
+    padding[15] = L::splat(512);
+    compress(&mut state, padding);
+
+    // Second hash of 32 bytes.
+    let mut block = [L::splat(0); 16];
+    block[..8].copy_from_slice(&state);
+    block[8] = L::splat(0x8000_0000);
+    block[15] = L::splat(256);
+    let mut state2 = INIT_HASH_VAL.map(L::splat);
+    compress(&mut state2, block);
+    state2
+}
+
+/// Double SHA-256 of many 64 bytes messages, such as concatenated child hashes of
+/// merkle tree nodes. `output[i]` is set to the hash of `input[i]`.
+/// Messages are hashed several at once.
+///
+/// # Panic
+/// when `output` and `input` have different lengths.
+pub fn sha256d_64(output: &mut [[u8; 32]], input: &[[u8; 64]]) {
+    assert_eq!(output.len(), input.len(), "output and input have different lengths");
+
+    #[cfg(target_arch = "x86_64")]
+    type Simd = self::sse2::Sse2;
+    #[cfg(not(target_arch = "x86_64"))]
+    type Simd = Ways;
+
+    let mut outs = output.chunks_exact_mut(WAYS);
+    let mut ins = input.chunks_exact(WAYS);
+    for (out, msgs) in (&mut outs).zip(&mut ins) {
+        // Transpose; `msg_block[j][i]` is `j`th word of `i`th message.
+        let mut msg_block = [[0; WAYS]; 16];
+        for (i, msg) in msgs.iter().enumerate() {
+            for (j, word) in msg_block.iter_mut().enumerate() {
+                word[i] = read_word(&msg[j * 4..]);
+            }
+        }
+        let hash_val = sha256d_64_lanes(msg_block.map(Simd::from)).map(Ways::from);
+        for (i, o) in out.iter_mut().enumerate() {
+            *o = parse_into_result(hash_val.map(|word| word[i]));
+        }
+    }
+
+    for (out, msg) in outs.into_remainder().iter_mut().zip(ins.remainder()) {
+        let mut msg_block: MsgBlock = [0; 16];
+        for (j, word) in msg_block.iter_mut().enumerate() {
+            *word = read_word(&msg[j * 4..]);
+        }
+        *out = parse_into_result(sha256d_64_lanes(msg_block));
+    }
 }
 
 mod word_ops {
-    use super::Word;
+    use super::Lane;
 
-    pub fn choose(x: Word, y: Word, z: Word) -> Word {
-        (x & y) ^ (!x & z)
+    #[inline(always)]
+    pub fn choose<L: Lane>(x: L, y: L, z: L) -> L {
+        x.and(y).xor(x.not().and(z))
     }
 
-    pub fn majority(x: Word, y: Word, z: Word) -> Word {
-        (x & y) ^ (x & z) ^ (y & z)
+    #[inline(always)]
+    pub fn majority<L: Lane>(x: L, y: L, z: L) -> L {
+        x.and(y).xor(x.and(z)).xor(y.and(z))
     }
 
     /// Represented as large sigma 0 to 256.
-    pub fn big_sigma_0(x: Word) -> Word {
-        x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)
+    #[inline(always)]
+    pub fn big_sigma_0<L: Lane>(x: L) -> L {
+        x.rotr(2).xor(x.rotr(13)).xor(x.rotr(22))
     }
 
-    pub fn big_sigma_1(x: Word) -> Word {
-        x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)
+    #[inline(always)]
+    pub fn big_sigma_1<L: Lane>(x: L) -> L {
+        x.rotr(6).xor(x.rotr(11)).xor(x.rotr(25))
     }
 
-    pub fn small_sigma_0(x: Word) -> Word {
-        x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+    #[inline(always)]
+    pub fn small_sigma_0<L: Lane>(x: L) -> L {
+        x.rotr(7).xor(x.rotr(18)).xor(x.shr(3))
     }
 
-    pub fn small_sigma_1(x: Word) -> Word {
-        x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+    #[inline(always)]
+    pub fn small_sigma_1<L: Lane>(x: L) -> L {
+        x.rotr(17).xor(x.rotr(19)).xor(x.shr(10))
     }
 }
 
@@ -205,15 +520,8 @@ mod word_ops {
 
 fn parse_into_result(hash_val: HashValue) -> [u8; 32] {
     let mut res = [0; 32];
-    for i in 0..8 {
-        let bytes = u32_to_bytes(hash_val[i]);
-        (&mut res[i * 4..(i + 1) * 4]).copy_from_slice(&bytes);
+    for (i, word) in hash_val.iter().enumerate() {
+        res[i * 4..(i + 1) * 4].copy_from_slice(&word.to_be_bytes());
     }
     res
 }
-
-fn u32_to_bytes(n: u32) -> [u8; 4] {
-    use std::mem::transmute;
-
-    unsafe { transmute::<u32, [u8; 4]>(n.to_be()) }
-}
@@ -0,0 +1,204 @@
+use bitcoinrs_bytes::encode::WriteBuffer;
+
+use hash::HashEngine;
+
+type Word = u64;
+type HashValue = [Word; 8];
+
+const BLOCK_SIZE: usize = 1024 / 8;
+
+pub fn sha512(msg: &[u8]) -> [u8; 64] {
+    let mut engine = Sha512::new();
+    engine.update(msg);
+    engine.finalize()
+}
+
+/* ===================================== */
+/* Engine */
+/* ===================================== */
+
+/// Streaming SHA-512 engine.
+///
+/// Like `Sha256`, it implements `WriteBuffer`.
+#[derive(Clone)]
+pub struct Sha512 {
+    hash_val: HashValue,
+    /// Bytes which do not fill a message block yet.
+    block: [u8; BLOCK_SIZE],
+    /// Total length of input in bytes.
+    length: u64,
+}
+
+impl Sha512 {
+    pub fn new() -> Sha512 {
+        Sha512 {
+            hash_val: INIT_HASH_VAL,
+            block: [0; BLOCK_SIZE],
+            length: 0,
+        }
+    }
+
+    /// Length of input so far in bytes.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        let block_len = self.block_len();
+        self.length += bytes.len() as u64;
+
+        // Fill pending block first.
+        if block_len > 0 {
+            let n = (BLOCK_SIZE - block_len).min(bytes.len());
+            self.block[block_len..block_len + n].copy_from_slice(&bytes[..n]);
+            bytes = &bytes[n..];
+            if block_len + n < BLOCK_SIZE {
+                return;
+            }
+            let block = self.block;
+            self.process(&block);
+        }
+
+        // Process whole blocks without copying.
+        let mut chunks = bytes.chunks_exact(BLOCK_SIZE);
+        for chunk in &mut chunks {
+            self.process(chunk);
+        }
+        let rest = chunks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+    }
+
+    pub fn finalize(mut self) -> [u8; 64] {
+        let bit_len = u128::from(self.length) * 8;
+
+        // Padding; a single 1 bit, zeros, and 128 bits length in bits.
+        let block_len = self.block_len();
+        let zeros = (BLOCK_SIZE * 2 - block_len - 1 - 16) % BLOCK_SIZE;
+        let mut padding = [0; BLOCK_SIZE * 2];
+        padding[0] = 0b_1000_0000;
+        padding[1 + zeros..1 + zeros + 16].copy_from_slice(&bit_len.to_be_bytes());
+        self.update(&padding[..1 + zeros + 16]);
+        debug_assert!(self.block_len() == 0);
+
+        let mut result = [0; 64];
+        for (i, word) in self.hash_val.iter().enumerate() {
+            result[i * 8..i * 8 + 8].copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+
+    fn block_len(&self) -> usize {
+        (self.length % BLOCK_SIZE as u64) as usize
+    }
+
+    fn process(&mut self, block: &[u8]) {
+        let mut w = [0; 80];
+        for (i, word) in w.iter_mut().take(16).enumerate() {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&block[i * 8..i * 8 + 8]);
+            *word = Word::from_be_bytes(bytes);
+        }
+        compress(&mut self.hash_val, &mut w);
+    }
+}
+
+impl Default for Sha512 {
+    fn default() -> Sha512 {
+        Sha512::new()
+    }
+}
+
+impl WriteBuffer for Sha512 {
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+
+    fn has_buffer(&self, _size: usize) -> bool {
+        true
+    }
+}
+
+impl HashEngine for Sha512 {
+    type Output = [u8; 64];
+
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = 64;
+
+    fn update(&mut self, bytes: &[u8]) {
+        Sha512::update(self, bytes);
+    }
+
+    fn finalize(self) -> [u8; 64] {
+        Sha512::finalize(self)
+    }
+}
+
+/* ===================================== */
+/* Computation */
+/* ===================================== */
+
+/// Sha-512 initial hash value.
+const INIT_HASH_VAL: HashValue = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+const SHA512_CONST_WORDS: [Word; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+/// Process a message block whose first 16 words are filled in `w`.
+/// The rest of `w` is used as the message schedule.
+fn compress(hash_val: &mut HashValue, w: &mut [Word; 80]) {
+    for t in 16..80 {
+        let s0 = w[t - 15].rotate_right(1) ^ w[t - 15].rotate_right(8) ^ (w[t - 15] >> 7);
+        let s1 = w[t - 2].rotate_right(19) ^ w[t - 2].rotate_right(61) ^ (w[t - 2] >> 6);
+        w[t] = w[t - 16].wrapping_add(s0).wrapping_add(w[t - 7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *hash_val;
+    for t in 0..80 {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA512_CONST_WORDS[t])
+            .wrapping_add(w[t]);
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (val, x) in hash_val.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+        *val = val.wrapping_add(*x);
+    }
+}
@@ -0,0 +1,159 @@
+extern crate bitcoinrs_bytes;
+extern crate bitcoinrs_crypto;
+
+use bitcoinrs_bytes::hex;
+use bitcoinrs_crypto::secp256k1::{PublicKey, RecoveryId, Scalar, Secp256k1Error, SecretKey, Signature};
+use bitcoinrs_crypto::sha256;
+
+struct TestVector {
+    secret: &'static str,
+    msg: &'static str,
+    pubkey: &'static str,
+    sig: &'static str,
+    recid: u8,
+    der: &'static str,
+}
+
+/// RFC6979 vectors widely used by Bitcoin libraries, with SHA-256 of `msg` as the message hash.
+const VECTORS: [TestVector; 4] = [
+    TestVector {
+        secret: "0000000000000000000000000000000000000000000000000000000000000001",
+        msg: "Satoshi Nakamoto",
+        pubkey: "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        sig: "934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d8\
+              2442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5",
+        recid: 1,
+        der: "3045022100934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d8\
+              02202442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5",
+    },
+    TestVector {
+        secret: "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140",
+        msg: "Satoshi Nakamoto",
+        pubkey: "0379be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        sig: "fd567d121db66e382991534ada77a6bd3106f0a1098c231e47993447cd6af2d0\
+              6b39cd0eb1bc8603e159ef5c20a5c8ad685a45b06ce9bebed3f153d10d93bed5",
+        recid: 0,
+        der: "3045022100fd567d121db66e382991534ada77a6bd3106f0a1098c231e47993447cd6af2d0\
+              02206b39cd0eb1bc8603e159ef5c20a5c8ad685a45b06ce9bebed3f153d10d93bed5",
+    },
+    TestVector {
+        secret: "f8b8af8ce3c7cca5e300d33939540c10d45ce001b8f252bfbc57ba0342904181",
+        msg: "Alan Turing",
+        pubkey: "0292df7b245b81aa637ab4e867c8d511008f79161a97d64f2ac709600352f7acbc",
+        sig: "7063ae83e7f62bbb171798131b4a0564b956930092b33b07b395615d9ec7e15c\
+              58dfcc1e00a35e1572f366ffe34ba0fc47db1e7189759b9fb233c5b05ab388ea",
+        recid: 0,
+        der: "304402207063ae83e7f62bbb171798131b4a0564b956930092b33b07b395615d9ec7e15c\
+              022058dfcc1e00a35e1572f366ffe34ba0fc47db1e7189759b9fb233c5b05ab388ea",
+    },
+    TestVector {
+        secret: "e91671c46231f833a6406ccbea0e3e392c76c167bac1cb013f6f1013980455c2",
+        msg: "There is a computer disease that anybody who works with computers knows about. \
+              It's a very serious disease and it interferes completely with the work. \
+              The trouble with computers is that you 'play' with them!",
+        pubkey: "03567b7512001f3cc4dcb8b8096c046fff571ab07adb2126cd42908f2ff1ca424a",
+        sig: "b552edd27580141f3b2a5463048cb7cd3e047b97c9f98076c32dbdf85a68718b\
+              279fa72dd19bfae05577e06c7c0c1900c371fcd5893f7e1d56a37d30174671f6",
+        recid: 1,
+        der: "3045022100b552edd27580141f3b2a5463048cb7cd3e047b97c9f98076c32dbdf85a68718b\
+              0220279fa72dd19bfae05577e06c7c0c1900c371fcd5893f7e1d56a37d30174671f6",
+    },
+];
+
+fn secret_key(hex: &str) -> SecretKey {
+    let mut bytes = [0; 32];
+    hex::decode_to_slice(hex, &mut bytes).unwrap();
+    SecretKey::from_bytes(&bytes).unwrap()
+}
+
+fn compact(hex: &str) -> Signature {
+    let mut bytes = [0; 64];
+    hex::decode_to_slice(hex, &mut bytes).unwrap();
+    Signature::from_compact(&bytes).unwrap()
+}
+
+#[test]
+fn sign_and_verify() {
+    for v in VECTORS.iter() {
+        let secret = secret_key(v.secret);
+        let pubkey = secret.public_key();
+        let msg = sha256(v.msg.as_bytes());
+        assert_eq!(hex::encode(&pubkey.serialize()), v.pubkey);
+
+        let (sig, recid) = secret.sign_ecdsa_recoverable(&msg);
+        assert_eq!(hex::encode(&sig.to_compact()), v.sig);
+        assert_eq!(recid.to_u8(), v.recid);
+        assert_eq!(hex::encode(&sig.to_der()), v.der);
+        assert_eq!(Signature::from_der(&sig.to_der()), Ok(sig));
+        assert!(sig.is_low_s());
+
+        assert!(pubkey.verify_ecdsa(&msg, &sig));
+        assert_eq!(PublicKey::recover_ecdsa(&msg, &sig, recid), Ok(pubkey));
+
+        // Wrong message or key.
+        let other = sha256(b"other message");
+        assert!(!pubkey.verify_ecdsa(&other, &sig));
+        assert_ne!(PublicKey::recover_ecdsa(&other, &sig, recid), Ok(pubkey));
+        let other_key = secret_key("0000000000000000000000000000000000000000000000000000000000000002");
+        assert!(!other_key.public_key().verify_ecdsa(&msg, &sig));
+    }
+}
+
+#[test]
+fn low_s() {
+    let v = &VECTORS[0];
+    let pubkey = secret_key(v.secret).public_key();
+    let msg = sha256(v.msg.as_bytes());
+    let sig = compact(v.sig);
+
+    // High `s` also verifies, but is not standard.
+    let high = Signature::new(*sig.r(), -*sig.s()).unwrap();
+    assert!(!high.is_low_s());
+    assert!(pubkey.verify_ecdsa(&msg, &high));
+    assert_eq!(high.normalize_s(), sig);
+    assert_eq!(sig.normalize_s(), sig);
+
+    // Recovery id of high `s` has flipped parity.
+    let flipped = RecoveryId::from_u8(v.recid ^ 1).unwrap();
+    assert_eq!(PublicKey::recover_ecdsa(&msg, &high, flipped), Ok(pubkey));
+}
+
+#[test]
+fn invalid_signatures() {
+    let invalid = Err(Secp256k1Error::InvalidSignature);
+    assert_eq!(Signature::new(Scalar::ZERO, Scalar::ONE), invalid);
+    assert_eq!(Signature::from_compact(&[0xff; 64]), invalid);
+    assert_eq!(RecoveryId::from_u8(4), Err(Secp256k1Error::InvalidRecoveryId));
+
+    let der = hex::decode(VECTORS[0].der).unwrap();
+    let mut cases = Vec::new();
+    // Wrong total length.
+    let mut bad = der.clone();
+    bad[1] += 1;
+    cases.push(bad);
+    // Trailing byte, such as a sighash type.
+    let mut bad = der.clone();
+    bad.push(0x01);
+    cases.push(bad);
+    // Negative `r`.
+    let mut bad = der.clone();
+    bad.remove(4);
+    bad[3] = 0x20;
+    bad[1] = 0x44;
+    cases.push(bad);
+    // Needless zero padding of `s`.
+    let mut bad = der.clone();
+    bad.insert(39, 0x00);
+    bad[38] = 0x21;
+    bad[1] = 0x46;
+    cases.push(bad);
+
+    for bad in cases.iter() {
+        assert_eq!(Signature::from_der(bad), invalid, "{}", hex::encode(bad));
+    }
+
+    // Small values are encoded in short form.
+    let sig = Signature::new(Scalar::ONE, Scalar::from_u64(0x80)).unwrap();
+    assert_eq!(hex::encode(&sig.to_der()), "300702010102020080");
+    assert_eq!(Signature::from_der(&sig.to_der()), Ok(sig));
+}
@@ -0,0 +1,54 @@
+extern crate bitcoinrs_bytes;
+extern crate bitcoinrs_crypto;
+
+use bitcoinrs_bytes::hex;
+use bitcoinrs_crypto::pbkdf2_hmac_sha512;
+
+/// Mnemonics and seeds from BIP39 reference vectors, all with passphrase "TREZOR".
+const BIP39_VECTORS: [(&str, &str); 5] = [
+    (
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+    ),
+    (
+        "legal winner thank year wave sausage worth useful legal winner thank yellow",
+        "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+    ),
+    (
+        "letter advice cage absurd amount doctor acoustic avoid letter advice cage above",
+        "d71de856f81a8acc65e6fc851a38d4d7ec216fd0796d0a6827a3ad6ed5511a30fa280f12eb2e47ed2ac03b5c462a0358d18d69fe4f985ec81778c1b370b652a8",
+    ),
+    (
+        "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+        "ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+    ),
+    (
+        "void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold",
+        "01f5bced59dec48e362f2c45b5de68b9fd6c92c6634f44d6d40aab69056506f0e35524a518034ddc1192e1dacd32c1ed3eaa3c3b131c88ed8e7e54c49a5d0998",
+    ),
+];
+
+#[test]
+fn bip39_seed_test() {
+    for &(mnemonic, seed) in BIP39_VECTORS.iter() {
+        let mut output = [0; 64];
+        pbkdf2_hmac_sha512(mnemonic.as_bytes(), b"mnemonicTREZOR", 2048, &mut output);
+        assert_eq!(hex::encode(&output), seed);
+    }
+}
+
+#[test]
+fn output_length_test() {
+    // Longer than a hash value, so that it spans two blocks.
+    let mut output = [0; 100];
+    pbkdf2_hmac_sha512(b"password", b"salt", 1, &mut output);
+    assert_eq!(
+        hex::encode(&output),
+        "867f70cf1ade02cff3752599a3a53dc4af34c7a669815ae5d513554e1c8cf252c02d470a285a0501bad999bfe943c08f\
+         050235d7d68b1da55e63f73b60a57fce7b532e206c2967d4c7d2ffa460539fc4d4e5eec70125d74c6c7cf86d25284f297907fcea"
+    );
+
+    let mut output = [0; 20];
+    pbkdf2_hmac_sha512(b"password", b"salt", 2, &mut output);
+    assert_eq!(hex::encode(&output), "e1d9c16aa681708a45f5c7c4e215ceb66e011a2e");
+}
@@ -0,0 +1,49 @@
+extern crate bitcoinrs_bytes;
+extern crate bitcoinrs_crypto;
+
+use bitcoinrs_bytes::hex;
+use bitcoinrs_crypto::{ripemd160, Ripemd160};
+
+/// Test vectors published with the RIPEMD-160 specification.
+const VECTORS: [(&str, &str); 8] = [
+    ("", "9c1185a5c5e9fc54612808977ee8f548b2258d31"),
+    ("a", "0bdc9d2d256b3ee9daae347be6f4dc835a467ffe"),
+    ("abc", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+    ("message digest", "5d0689ef49d2fae572b881b123a85ffa21595f36"),
+    ("abcdefghijklmnopqrstuvwxyz", "f71c27109c692c1b56bbdceb5b9d2865b3708dbc"),
+    (
+        "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        "12a053384a9c0c88e405a06c27dcf49ada62eb2b",
+    ),
+    (
+        "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+        "b0e20b6e3116640286ed3a87a5713079b21f5189",
+    ),
+    (
+        "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+        "9b752e45573d4b39f4dbd3323cab82bf63326bfb",
+    ),
+];
+
+#[test]
+fn ripemd160_test() {
+    for &(msg, expected) in VECTORS.iter() {
+        assert_eq!(hex::encode(&ripemd160(msg.as_bytes())), expected);
+    }
+}
+
+#[test]
+fn ripemd160_streaming_test() {
+    // One million of "a", fed in chunks which do not align to blocks.
+    let mut engine = Ripemd160::new();
+    let chunk = [b'a'; 1000];
+    for _ in 0..1000 {
+        engine.update(&chunk[..999]);
+        engine.update(&chunk[999..]);
+    }
+    assert_eq!(engine.length(), 1_000_000);
+    assert_eq!(
+        hex::encode(&engine.finalize()),
+        "52783243c1697bdbe16d37f97f68f08325dc1528"
+    );
+}
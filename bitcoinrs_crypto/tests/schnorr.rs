@@ -0,0 +1,70 @@
+extern crate bitcoinrs_bytes;
+extern crate bitcoinrs_crypto;
+
+use bitcoinrs_bytes::hex;
+use bitcoinrs_crypto::secp256k1::{SchnorrSignature, SecretKey, XOnlyPublicKey};
+
+/// Official test vectors of BIP340.
+const VECTORS: &str = include_str!("bip340_vectors.csv");
+
+fn array32(hex: &str) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    hex::decode_to_slice(hex, &mut bytes).unwrap();
+    bytes
+}
+
+fn array64(hex: &str) -> [u8; 64] {
+    let mut bytes = [0; 64];
+    hex::decode_to_slice(hex, &mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn bip340_vectors() {
+    assert_eq!(VECTORS.lines().count(), 20);
+    for line in VECTORS.lines().skip(1) {
+        let cols: Vec<&str> = line.splitn(8, ',').collect();
+        let (index, secret, pubkey, aux_rand) = (cols[0], cols[1], cols[2], cols[3]);
+        let msg = hex::decode(cols[4]).unwrap();
+        let sig = array64(cols[5]);
+        let expected = cols[6] == "TRUE";
+
+        if !secret.is_empty() {
+            let secret = SecretKey::from_bytes(&array32(secret)).unwrap();
+            let (xonly, _) = secret.public_key().x_only_public_key();
+            assert_eq!(xonly.serialize(), array32(pubkey), "vector {}", index);
+
+            let signed = secret.sign_schnorr(&msg, &array32(aux_rand));
+            assert_eq!(signed.to_bytes()[..], sig[..], "vector {}", index);
+        }
+
+        // Public keys and signatures out of range fail to parse rather than to verify.
+        let verified = match (XOnlyPublicKey::from_bytes(&array32(pubkey)), SchnorrSignature::from_bytes(&sig)) {
+            (Ok(pubkey), Ok(sig)) => pubkey.verify_schnorr(&msg, &sig),
+            _ => false,
+        };
+        assert_eq!(verified, expected, "vector {}: {}", index, cols[7]);
+    }
+}
+
+#[test]
+fn odd_public_key() {
+    // Public key of secret key 3 has even y, and its negation has odd y.
+    let three = "0000000000000000000000000000000000000000000000000000000000000003";
+    let minus_three = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd036413e";
+    let three = SecretKey::from_bytes(&array32(three)).unwrap();
+    let minus_three = SecretKey::from_bytes(&array32(minus_three)).unwrap();
+
+    let (xonly, odd) = three.public_key().x_only_public_key();
+    assert!(!odd);
+    let (negated, odd) = minus_three.public_key().x_only_public_key();
+    assert!(odd);
+    assert_eq!(xonly, negated);
+
+    // Both sign for the same x-only public key.
+    let msg = b"odd public key";
+    let sig = minus_three.sign_schnorr(msg, &[0; 32]);
+    assert!(xonly.verify_schnorr(msg, &sig));
+    assert_eq!(sig, three.sign_schnorr(msg, &[0; 32]));
+    assert!(!xonly.verify_schnorr(b"other message", &sig));
+}
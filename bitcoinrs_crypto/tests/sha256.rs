@@ -1,3 +1,4 @@
+extern crate bitcoinrs_bytes;
 extern crate bitcoinrs_crypto;
 extern crate openssl;
 extern crate rand;
@@ -5,7 +6,10 @@ extern crate rand;
 use rand::{OsRng, Rng};
 
 use openssl::sha::sha256 as os_sha256;
+use bitcoinrs_bytes::encode::WriteBuffer;
+use bitcoinrs_bytes::endian::u32_l;
 use bitcoinrs_crypto::sha256 as btc_sha256;
+use bitcoinrs_crypto::{sha256d_64, Sha256};
 
 #[test]
 fn sha256_test() {
@@ -18,6 +22,67 @@ fn sha256_test() {
     }
 }
 
+#[test]
+fn sha256_streaming_test() {
+    let mut rng = OsRng::new().unwrap();
+    for _ in 0..100 {
+        let buf = gen_random_vec(&mut rng);
+
+        // Feed in random sized chunks.
+        let mut engine = Sha256::new();
+        let mut rest = buf.as_slice();
+        while !rest.is_empty() {
+            let n = (rng.next_u32() as usize % 200).min(rest.len());
+            engine.update(&rest[..n]);
+            rest = &rest[n..];
+        }
+        assert_eq!(engine.finalize(), os_sha256(buf.as_slice()));
+    }
+
+    // Hash `Encodable` through `WriteBuffer`.
+    let mut engine = Sha256::new();
+    engine.write(u32_l::new(0x64636261));
+    assert_eq!(engine.finalize(), os_sha256(b"abcd"));
+}
+
+#[test]
+fn sha256_midstate_test() {
+    let msg = [0x5a; 200];
+
+    let mut engine = Sha256::new();
+    engine.update(&msg[..128]);
+    let midstate = engine.midstate();
+
+    let mut resumed = Sha256::from_midstate(midstate, 128);
+    resumed.update(&msg[128..]);
+    assert_eq!(resumed.length(), 200);
+    assert_eq!(resumed.finalize(), os_sha256(&msg));
+
+    // Midstate of empty input is the initial hash value.
+    assert_eq!(
+        Sha256::new().midstate()[..8],
+        [0x6a, 0x09, 0xe6, 0x67, 0xbb, 0x67, 0xae, 0x85]
+    );
+}
+
+#[test]
+fn sha256d_64_test() {
+    let mut rng = OsRng::new().unwrap();
+    // Cover both of messages hashed at once and the rest.
+    for n in 0..11 {
+        let mut input = vec![[0; 64]; n];
+        for msg in input.iter_mut() {
+            rng.fill_bytes(&mut msg[..]);
+        }
+        let mut output = vec![[0; 32]; n];
+        sha256d_64(&mut output, &input);
+
+        for (out, msg) in output.iter().zip(input.iter()) {
+            assert_eq!(*out, os_sha256(&os_sha256(&msg[..])));
+        }
+    }
+}
+
 fn gen_random_vec(rng: &mut OsRng) -> Vec<u8> {
     let len = rng.next_u32() % 10000;
     let mut vec = Vec::with_capacity(len as usize);
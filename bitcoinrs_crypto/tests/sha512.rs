@@ -0,0 +1,45 @@
+extern crate bitcoinrs_crypto;
+extern crate openssl;
+extern crate rand;
+
+use rand::{OsRng, Rng};
+
+use openssl::sha::sha512 as os_sha512;
+use bitcoinrs_crypto::{sha512, Sha512};
+
+#[test]
+fn sha512_test() {
+    assert_eq!(sha512(b"hoge")[..], os_sha512(b"hoge")[..]);
+
+    let mut rng = OsRng::new().unwrap();
+    for _ in 0..1000 {
+        let buf = gen_random_vec(&mut rng);
+        assert_eq!(sha512(&buf)[..], os_sha512(&buf)[..]);
+    }
+}
+
+#[test]
+fn sha512_streaming_test() {
+    let mut rng = OsRng::new().unwrap();
+    for _ in 0..100 {
+        let buf = gen_random_vec(&mut rng);
+
+        // Feed in random sized chunks.
+        let mut engine = Sha512::new();
+        let mut rest = buf.as_slice();
+        while !rest.is_empty() {
+            let n = (rng.next_u32() as usize % 300).min(rest.len());
+            engine.update(&rest[..n]);
+            rest = &rest[n..];
+        }
+        assert_eq!(engine.length(), buf.len() as u64);
+        assert_eq!(engine.finalize()[..], os_sha512(&buf)[..]);
+    }
+}
+
+fn gen_random_vec(rng: &mut OsRng) -> Vec<u8> {
+    let len = rng.next_u32() % 10000;
+    let mut vec = vec![0; len as usize];
+    rng.fill_bytes(vec.as_mut_slice());
+    vec
+}
@@ -0,0 +1,259 @@
+//! Small JSON library, since we use only standard library.
+//!
+//! Types are converted from/into `Value` through `ToJson` and `FromJson`,
+//! and `Value` is written by `Display` or parsed by `parse`.
+
+mod value;
+mod write;
+mod parse;
+
+pub use value::{Number, Value};
+pub use parse::{parse, ParseError, ParseErrorKind, MAX_DEPTH};
+
+use std::error::Error;
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+pub trait FromJson: Sized {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError>;
+
+    /// Parse JSON text and convert it.
+    fn from_json_str(s: &str) -> Result<Self, FromJsonError> {
+        let value = parse(s).map_err(FromJsonErrorKind::Parse)?;
+        Self::from_json(&value)
+    }
+}
+
+/// Get member `key` of an object and convert it.
+/// A missing member is treated as `null`, so `Option<T>` accepts it.
+pub fn field<T: FromJson>(value: &Value, key: &'static str) -> Result<T, FromJsonError> {
+    if value.as_object().is_none() {
+        return Err(FromJsonError::invalid_type("object", value));
+    }
+    match value.get(key) {
+        Some(member) => T::from_json(member),
+        None => T::from_json(&Value::Null).map_err(|_| FromJsonErrorKind::MissingField.into()),
+    }.map_err(|e| e.context(key))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromJsonError {
+    kind: FromJsonErrorKind,
+    /// Innermost member comes first.
+    path: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FromJsonErrorKind {
+    Parse(ParseError),
+    MissingField,
+    InvalidType {
+        expected: &'static str,
+        actual: &'static str,
+    },
+    /// Type is correct but the value is not acceptable, e.g. out of range.
+    InvalidValue(String),
+}
+
+impl FromJsonError {
+    pub fn new(kind: FromJsonErrorKind) -> FromJsonError {
+        FromJsonError {
+            kind,
+            path: Vec::new(),
+        }
+    }
+
+    pub fn invalid_type(expected: &'static str, actual: &Value) -> FromJsonError {
+        FromJsonError::new(FromJsonErrorKind::InvalidType {
+            expected,
+            actual: actual.type_name(),
+        })
+    }
+
+    pub fn invalid_value<S: Into<String>>(msg: S) -> FromJsonError {
+        FromJsonError::new(FromJsonErrorKind::InvalidValue(msg.into()))
+    }
+
+    pub fn kind(&self) -> &FromJsonErrorKind {
+        &self.kind
+    }
+
+    /// Members from outermost to innermost where the error is found.
+    pub fn path(&self) -> Vec<&'static str> {
+        self.path.iter().rev().cloned().collect()
+    }
+
+    /// Add a member name as an outer context.
+    pub fn context(mut self, key: &'static str) -> FromJsonError {
+        self.path.push(key);
+        self
+    }
+}
+
+impl From<FromJsonErrorKind> for FromJsonError {
+    fn from(kind: FromJsonErrorKind) -> FromJsonError {
+        FromJsonError::new(kind)
+    }
+}
+
+impl Display for FromJsonError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match self.kind {
+            FromJsonErrorKind::Parse(ref e) => write!(f, "{}", e)?,
+            FromJsonErrorKind::MissingField => write!(f, "missing field")?,
+            FromJsonErrorKind::InvalidType { expected, actual } => {
+                write!(f, "expected {} but found {}", expected, actual)?
+            }
+            FromJsonErrorKind::InvalidValue(ref msg) => write!(f, "invalid value: {}", msg)?,
+        }
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path().join("."))?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for FromJsonError {}
+
+impl ToJson for Value {
+    fn to_json(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl FromJson for Value {
+    fn from_json(value: &Value) -> Result<Value, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &Value) -> Result<bool, FromJsonError> {
+        value
+            .as_bool()
+            .ok_or_else(|| FromJsonError::invalid_type("bool", value))
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl ToJson for &str {
+    fn to_json(&self) -> Value {
+        Value::from(*self)
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &Value) -> Result<String, FromJsonError> {
+        value
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| FromJsonError::invalid_type("string", value))
+    }
+}
+
+macro_rules! impl_json_for_int {
+    ( $( $t: ty => $as_int: ident ),* ) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> Value {
+                    Value::from(*self)
+                }
+            }
+
+            impl FromJson for $t {
+                fn from_json(value: &Value) -> Result<$t, FromJsonError> {
+                    let n = value
+                        .as_number()
+                        .ok_or_else(|| FromJsonError::invalid_type("number", value))?;
+                    n.$as_int()
+                        .and_then(|n| ::std::convert::TryFrom::try_from(n).ok())
+                        .ok_or_else(|| {
+                            let msg = format!("{} is not {}", n, stringify!($t));
+                            FromJsonError::invalid_value(msg)
+                        })
+                }
+            }
+        )*
+    };
+}
+
+impl_json_for_int!(
+    u8 => as_u64, u16 => as_u64, u32 => as_u64, u64 => as_u64, usize => as_u64,
+    i8 => as_i64, i16 => as_i64, i32 => as_i64, i64 => as_i64
+);
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value) -> Result<Vec<T>, FromJsonError> {
+        value
+            .as_array()
+            .ok_or_else(|| FromJsonError::invalid_type("array", value))?
+            .iter()
+            .map(T::from_json)
+            .collect()
+    }
+}
+
+/// `None` is `null`.
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        match *self {
+            Some(ref t) => t.to_json(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &Value) -> Result<Option<T>, FromJsonError> {
+        match *value {
+            Value::Null => Ok(None),
+            ref v => T::from_json(v).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields() {
+        let v = parse(r#"{"n": 300, "s": "abc", "a": [1, 2], "o": null}"#).unwrap();
+        assert_eq!(field::<u16>(&v, "n"), Ok(300));
+        assert_eq!(field::<String>(&v, "s"), Ok("abc".to_string()));
+        assert_eq!(field::<Vec<u8>>(&v, "a"), Ok(vec![1, 2]));
+        assert_eq!(field::<Option<u8>>(&v, "o"), Ok(None));
+        assert_eq!(field::<Option<u8>>(&v, "missing"), Ok(None));
+
+        let e = field::<u8>(&v, "n").unwrap_err();
+        assert_eq!(e.kind(), &FromJsonErrorKind::InvalidValue("300 is not u8".to_string()));
+        assert_eq!(e.path(), vec!["n"]);
+        assert_eq!(e.to_string(), "invalid value: 300 is not u8 at n");
+
+        let e = field::<u8>(&v, "missing").unwrap_err();
+        assert_eq!(e.kind(), &FromJsonErrorKind::MissingField);
+
+        let e = field::<bool>(&v, "s").unwrap_err();
+        assert_eq!(e.to_string(), "expected bool but found string at s");
+    }
+}
@@ -0,0 +1,368 @@
+use std::error::Error;
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use value::{Number, Value};
+
+/// Maximum nesting of arrays and objects, the same as Bitcoin Core's `MAX_JSON_DEPTH`.
+pub const MAX_DEPTH: usize = 512;
+
+/// Parse JSON text. Whitespace around the value is allowed, but nothing else.
+pub fn parse(s: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser {
+        s: s.as_bytes(),
+        pos: 0,
+    };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != s.len() {
+        return Err(parser.error(ParseErrorKind::TrailingCharacters));
+    }
+    Ok(value)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    index: usize,
+}
+
+impl ParseError {
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Byte index in the input where the error is found.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    UnexpectedEof,
+    UnexpectedChar(char),
+    InvalidNumber,
+    InvalidEscape,
+    /// `\u` escape of a lone surrogate.
+    InvalidUnicode,
+    /// Nested deeper than `MAX_DEPTH`.
+    TooDeep,
+    TrailingCharacters,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        match self.kind {
+            ParseErrorKind::UnexpectedEof => write!(f, "unexpected end of input")?,
+            ParseErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c)?,
+            ParseErrorKind::InvalidNumber => write!(f, "invalid number")?,
+            ParseErrorKind::InvalidEscape => write!(f, "invalid escape sequence")?,
+            ParseErrorKind::InvalidUnicode => write!(f, "invalid unicode code point")?,
+            ParseErrorKind::TooDeep => write!(f, "nested deeper than {}", MAX_DEPTH)?,
+            ParseErrorKind::TrailingCharacters => write!(f, "trailing characters")?,
+        }
+        write!(f, " at index {}", self.index)
+    }
+}
+
+impl Error for ParseError {}
+
+struct Parser<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn value(&mut self, depth: usize) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'n' => self.literal("null", Value::Null),
+            b't' => self.literal("true", Value::Bool(true)),
+            b'f' => self.literal("false", Value::Bool(false)),
+            b'"' => Ok(Value::String(self.string()?)),
+            b'-' | b'0'..=b'9' => Ok(Value::Number(self.number()?)),
+            b'[' => {
+                self.check_depth(depth)?;
+                self.pos += 1;
+                let mut values = Vec::new();
+                if !self.eat(b']')? {
+                    loop {
+                        values.push(self.value(depth + 1)?);
+                        if self.eat(b']')? {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+                Ok(Value::Array(values))
+            }
+            b'{' => {
+                self.check_depth(depth)?;
+                self.pos += 1;
+                let mut members = Vec::new();
+                if !self.eat(b'}')? {
+                    loop {
+                        self.skip_whitespace();
+                        if self.peek()? != b'"' {
+                            return Err(self.unexpected_char());
+                        }
+                        let key = self.string()?;
+                        self.expect(b':')?;
+                        members.push((key, self.value(depth + 1)?));
+                        if self.eat(b'}')? {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+                Ok(Value::Object(members))
+            }
+            _ => Err(self.unexpected_char()),
+        }
+    }
+
+    fn check_depth(&self, depth: usize) -> Result<(), ParseError> {
+        if depth >= MAX_DEPTH {
+            return Err(self.error(ParseErrorKind::TooDeep));
+        }
+        Ok(())
+    }
+
+    fn literal(&mut self, lit: &str, value: Value) -> Result<Value, ParseError> {
+        for &c in lit.as_bytes() {
+            if self.peek()? != c {
+                return Err(self.unexpected_char());
+            }
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Number, ParseError> {
+        let start = self.pos;
+        self.eat_byte(b'-');
+
+        // Integer part; no leading zeros.
+        if !self.eat_byte(b'0') && self.eat_digits() == 0 {
+            return Err(self.error(ParseErrorKind::InvalidNumber));
+        }
+        if self.eat_byte(b'.') && self.eat_digits() == 0 {
+            return Err(self.error(ParseErrorKind::InvalidNumber));
+        }
+        if self.eat_byte(b'e') || self.eat_byte(b'E') {
+            if !self.eat_byte(b'+') {
+                self.eat_byte(b'-');
+            }
+            if self.eat_digits() == 0 {
+                return Err(self.error(ParseErrorKind::InvalidNumber));
+            }
+        }
+
+        // Never fails because it contains only ascii.
+        let text = ::std::str::from_utf8(&self.s[start..self.pos]).unwrap();
+        Ok(Number::from_text(text.to_string()))
+    }
+
+    fn eat_digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.pos < self.s.len() && self.s[self.pos].is_ascii_digit() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        // Skip '"'
+        self.pos += 1;
+
+        let mut bytes = Vec::new();
+        loop {
+            let c = self.peek()?;
+            match c {
+                b'"' => {
+                    self.pos += 1;
+                    break;
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    let c = match self.peek()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{08}',
+                        b'f' => '\u{0c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            self.pos += 1;
+                            let c = self.unicode_escape()?;
+                            let mut utf8 = [0; 4];
+                            bytes.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+                            continue;
+                        }
+                        _ => return Err(self.error(ParseErrorKind::InvalidEscape)),
+                    };
+                    self.pos += 1;
+                    bytes.push(c as u8);
+                }
+                c if c < 0x20 => return Err(self.unexpected_char()),
+                c => {
+                    self.pos += 1;
+                    bytes.push(c);
+                }
+            }
+        }
+
+        // Never fails because input is `str` and escapes are valid chars.
+        Ok(String::from_utf8(bytes).unwrap())
+    }
+
+    /// Read `XXXX` after `\u`, and a following low surrogate if any.
+    fn unicode_escape(&mut self) -> Result<char, ParseError> {
+        let start = self.pos - 2;
+        let hi = self.hex4()?;
+        let code = match hi {
+            0xD800..=0xDBFF => {
+                if !(self.eat_byte(b'\\') && self.eat_byte(b'u')) {
+                    return Err(ParseError {
+                        kind: ParseErrorKind::InvalidUnicode,
+                        index: start,
+                    });
+                }
+                let lo = self.hex4()?;
+                if !(0xDC00..=0xDFFF).contains(&lo) {
+                    return Err(ParseError {
+                        kind: ParseErrorKind::InvalidUnicode,
+                        index: start,
+                    });
+                }
+                0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)
+            }
+            _ => hi,
+        };
+        ::std::char::from_u32(code).ok_or(ParseError {
+            kind: ParseErrorKind::InvalidUnicode,
+            index: start,
+        })
+    }
+
+    fn hex4(&mut self) -> Result<u32, ParseError> {
+        let mut n = 0;
+        for _ in 0..4 {
+            let d = match self.peek()? {
+                c @ b'0'..=b'9' => c - b'0',
+                c @ b'a'..=b'f' => c - b'a' + 10,
+                c @ b'A'..=b'F' => c - b'A' + 10,
+                _ => return Err(self.error(ParseErrorKind::InvalidEscape)),
+            };
+            n = (n << 4) | d as u32;
+            self.pos += 1;
+        }
+        Ok(n)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.s.len() {
+            match self.s[self.pos] {
+                b' ' | b'\t' | b'\n' | b'\r' => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn peek(&self) -> Result<u8, ParseError> {
+        match self.s.get(self.pos) {
+            Some(c) => Ok(*c),
+            None => Err(self.error(ParseErrorKind::UnexpectedEof)),
+        }
+    }
+
+    fn eat_byte(&mut self, c: u8) -> bool {
+        if self.s.get(self.pos) == Some(&c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skip whitespace and eat `c` if it is the next character.
+    fn eat(&mut self, c: u8) -> Result<bool, ParseError> {
+        self.skip_whitespace();
+        self.peek()?;
+        Ok(self.eat_byte(c))
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), ParseError> {
+        if !self.eat(c)? {
+            return Err(self.unexpected_char());
+        }
+        Ok(())
+    }
+
+    fn unexpected_char(&self) -> ParseError {
+        // `pos` is always at a char boundary because only ascii is skipped byte by byte.
+        let c = match ::std::str::from_utf8(&self.s[self.pos..]) {
+            Ok(rest) => rest.chars().next(),
+            Err(_) => None,
+        };
+        match c {
+            Some(c) => self.error(ParseErrorKind::UnexpectedChar(c)),
+            None => self.error(ParseErrorKind::UnexpectedEof),
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            index: self.pos,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_values() {
+        let s = r#" {"a": [1, -0.5e+3, true, null], "b\u00e9\ud83d\ude00": "x\ty", "c": {}} "#;
+        let v = parse(s).unwrap();
+        let a = v.get("a").unwrap().as_array().unwrap();
+        assert_eq!(a[0].as_number().unwrap().as_u64(), Some(1));
+        assert_eq!(a[1].as_number().unwrap().as_f64(), -500.0);
+        assert_eq!(a[2], Value::Bool(true));
+        assert!(a[3].is_null());
+        assert_eq!(v.get("bé😀").unwrap().as_str(), Some("x\ty"));
+        assert_eq!(v.get("c"), Some(&Value::Object(vec![])));
+
+        // Writing it back keeps numbers as they are.
+        assert_eq!(
+            v.to_string(),
+            r#"{"a":[1,-0.5e+3,true,null],"bé😀":"x\ty","c":{}}"#
+        );
+        assert_eq!(parse(&v.to_pretty_string(4)).unwrap(), v);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |s: &str| {
+            let e = parse(s).unwrap_err();
+            (e.kind(), e.index())
+        };
+        assert_eq!(err("[1,]"), (ParseErrorKind::UnexpectedChar(']'), 3));
+        assert_eq!(err("01"), (ParseErrorKind::TrailingCharacters, 1));
+        assert_eq!(err("1."), (ParseErrorKind::InvalidNumber, 2));
+        assert_eq!(err("{\"a\" 1}"), (ParseErrorKind::UnexpectedChar('1'), 5));
+        assert_eq!(err("\"\\x\""), (ParseErrorKind::InvalidEscape, 2));
+        assert_eq!(err("\"\\ud800\""), (ParseErrorKind::InvalidUnicode, 1));
+        assert_eq!(err("\"abc"), (ParseErrorKind::UnexpectedEof, 4));
+        assert_eq!(err("tru"), (ParseErrorKind::UnexpectedEof, 3));
+
+        let deep = "[".repeat(MAX_DEPTH + 1);
+        assert_eq!(err(&deep), (ParseErrorKind::TooDeep, MAX_DEPTH));
+        let ok = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&ok).is_ok());
+    }
+}
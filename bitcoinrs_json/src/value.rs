@@ -0,0 +1,201 @@
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+/// JSON value.
+///
+/// Members of an object keep their order, so output looks the same as Bitcoin Core's.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Object with members in the given order.
+    pub fn object(members: Vec<(&str, Value)>) -> Value {
+        Value::Object(members.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+
+    /// Member of an object. Returns `None` if `self` is not an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match *self {
+            Value::Object(ref members) => members.iter().find(|m| m.0 == key).map(|m| &m.1),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == Value::Null
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match *self {
+            Value::Number(ref n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref values) => Some(values.as_slice()),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, Value)]> {
+        match *self {
+            Value::Object(ref members) => Some(members.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Name of the JSON type, used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+}
+
+/// JSON number.
+///
+/// It keeps the decimal text as is, so no precision is lost between parsing and writing.
+/// This matters for amounts, which Bitcoin Core shows with 8 decimal places.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number(String);
+
+impl Number {
+    /// Caller must make sure that `s` is a valid JSON number.
+    pub(crate) fn from_text(s: String) -> Number {
+        Number(s)
+    }
+
+    /// Fixed point number such as an amount in BTC; `value / 10^decimals`.
+    pub fn fixed(value: i64, decimals: u32) -> Number {
+        if decimals == 0 {
+            return Number(value.to_string());
+        }
+        let abs = value.unsigned_abs().to_string();
+        let decimals = decimals as usize;
+        let padded = format!("{:0>width$}", abs, width = decimals + 1);
+        let (int, frac) = padded.split_at(padded.len() - decimals);
+        let sign = if value < 0 { "-" } else { "" };
+        Number(format!("{}{}.{}", sign, int, frac))
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// `None` if it is not an integer or out of range.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.0.parse().ok()
+    }
+
+    /// `None` if it is not an integer or out of range.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.0.parse().ok()
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        // Never fails because it is a valid JSON number.
+        self.0.parse().unwrap()
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.write_str(self.0.as_str())
+    }
+}
+
+macro_rules! impl_number_from_int {
+    ( $( $t: ty ),* ) => {
+        $(
+            impl From<$t> for Number {
+                fn from(n: $t) -> Number {
+                    Number(n.to_string())
+                }
+            }
+
+            impl From<$t> for Value {
+                fn from(n: $t) -> Value {
+                    Value::Number(Number::from(n))
+                }
+            }
+        )*
+    };
+}
+
+impl_number_from_int!(u8, u16, u32, u64, i8, i16, i32, i64, usize);
+
+/// Non finite number is converted to `null`, as Bitcoin Core does.
+impl From<f64> for Value {
+    fn from(n: f64) -> Value {
+        if n.is_finite() {
+            Value::Number(Number(n.to_string()))
+        } else {
+            Value::Null
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(s: &'a str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(values: Vec<Value>) -> Value {
+        Value::Array(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_number() {
+        assert_eq!(Number::fixed(100_000, 8).as_str(), "0.00100000");
+        assert_eq!(Number::fixed(-2_100_000_000_000_000, 8).as_str(), "-21000000.00000000");
+        assert_eq!(Number::fixed(1, 0).as_str(), "1");
+        assert_eq!(Number::fixed(100_000, 8).as_f64(), 0.001);
+        assert_eq!(Number::fixed(100_000, 8).as_u64(), None);
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::{Display, Error as FmtError, Formatter, Write};
+
+use value::Value;
+
+/// Compact JSON text without any whitespace.
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write_value(f, self, None, 0)
+    }
+}
+
+impl Value {
+    /// JSON text indented by `indent` spaces, as `bitcoin-cli` shows.
+    pub fn to_pretty_string(&self, indent: usize) -> String {
+        let mut s = String::new();
+        // Writing into String never fails.
+        write_value(&mut s, self, Some(indent), 0).unwrap();
+        s
+    }
+}
+
+fn write_value<W: Write>(
+    w: &mut W,
+    value: &Value,
+    indent: Option<usize>,
+    level: usize,
+) -> Result<(), FmtError> {
+    match *value {
+        Value::Null => w.write_str("null"),
+        Value::Bool(b) => w.write_str(if b { "true" } else { "false" }),
+        Value::Number(ref n) => w.write_str(n.as_str()),
+        Value::String(ref s) => write_string(w, s),
+        Value::Array(ref values) => {
+            if values.is_empty() {
+                return w.write_str("[]");
+            }
+            w.write_char('[')?;
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    w.write_char(',')?;
+                }
+                write_newline(w, indent, level + 1)?;
+                write_value(w, v, indent, level + 1)?;
+            }
+            write_newline(w, indent, level)?;
+            w.write_char(']')
+        }
+        Value::Object(ref members) => {
+            if members.is_empty() {
+                return w.write_str("{}");
+            }
+            w.write_char('{')?;
+            for (i, (k, v)) in members.iter().enumerate() {
+                if i > 0 {
+                    w.write_char(',')?;
+                }
+                write_newline(w, indent, level + 1)?;
+                write_string(w, k)?;
+                w.write_str(if indent.is_some() { ": " } else { ":" })?;
+                write_value(w, v, indent, level + 1)?;
+            }
+            write_newline(w, indent, level)?;
+            w.write_char('}')
+        }
+    }
+}
+
+fn write_newline<W: Write>(w: &mut W, indent: Option<usize>, level: usize) -> Result<(), FmtError> {
+    if let Some(indent) = indent {
+        w.write_char('\n')?;
+        for _ in 0..indent * level {
+            w.write_char(' ')?;
+        }
+    }
+    Ok(())
+}
+
+fn write_string<W: Write>(w: &mut W, s: &str) -> Result<(), FmtError> {
+    w.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\u{08}' => w.write_str("\\b")?,
+            '\u{0c}' => w.write_str("\\f")?,
+            c if (c as u32) < 0x20 || c == '\u{7f}' => write!(w, "\\u{:04x}", c as u32)?,
+            c => w.write_char(c)?,
+        }
+    }
+    w.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use value::Number;
+
+    fn sample() -> Value {
+        Value::Object(vec![
+            ("txid".to_string(), Value::from("ab\"\\\n\u{1}")),
+            ("value".to_string(), Value::Number(Number::fixed(5_000_000_000, 8))),
+            ("vin".to_string(), Value::Array(vec![Value::from(1), Value::Null])),
+            ("vout".to_string(), Value::Array(vec![])),
+            ("coinbase".to_string(), Value::from(true)),
+        ])
+    }
+
+    #[test]
+    fn write_compact() {
+        assert_eq!(
+            sample().to_string(),
+            r#"{"txid":"ab\"\\\n\u0001","value":50.00000000,"vin":[1,null],"vout":[],"coinbase":true}"#
+        );
+    }
+
+    #[test]
+    fn write_pretty() {
+        let expected = r#"{
+  "txid": "ab\"\\\n\u0001",
+  "value": 50.00000000,
+  "vin": [
+    1,
+    null
+  ],
+  "vout": [],
+  "coinbase": true
+}"#;
+        assert_eq!(sample().to_pretty_string(2), expected);
+    }
+}
@@ -0,0 +1,58 @@
+#![feature(test)]
+extern crate test;
+use test::Bencher;
+
+extern crate bitcoinrs_bytes;
+extern crate bitcoinrs_crypto;
+extern crate bitcoinrs_net;
+use bitcoinrs_bytes::encode::{Encodable, WriteBuffer};
+use bitcoinrs_bytes::decode::{DecodeError, Decodable, ReadBuffer};
+use bitcoinrs_crypto::sha256;
+use bitcoinrs_net::codec::NetworkType;
+use bitcoinrs_net::codec::msg::MsgPayload;
+
+const BLOCK_SIZE: usize = 4 * 1000 * 1000;
+
+/// Payload as large as a full block.
+struct LargePayload(Vec<u8>);
+
+impl Encodable for LargePayload {
+    fn length(&self) -> usize {
+        self.0.len()
+    }
+
+    fn encode<W: WriteBuffer>(&self, buf: &mut W) {
+        buf.write_bytes(self.0.as_slice())
+    }
+}
+
+impl Decodable for LargePayload {
+    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<Self, DecodeError> {
+        Ok(LargePayload(buf.read_bytes(BLOCK_SIZE)?.to_vec()))
+    }
+}
+
+impl MsgPayload for LargePayload {
+    const COMMAND_BYTES: [u8; 12] = [0x62, 0x6c, 0x6f, 0x63, 0x6b, 0, 0, 0, 0, 0, 0, 0];
+}
+
+#[bench]
+fn encode_large_msg(b: &mut Bencher) {
+    let msg = LargePayload(vec![0; BLOCK_SIZE]).into_msg(NetworkType::Main);
+    b.iter(|| msg.to_vec());
+}
+
+/// Old way; encode payload into a temporary buffer to compute checksum and copy it.
+#[bench]
+fn encode_large_msg_double_buffered(b: &mut Bencher) {
+    let msg = LargePayload(vec![0; BLOCK_SIZE]).into_msg(NetworkType::Main);
+    b.iter(|| {
+        let payload = msg.payload().to_vec();
+        let hash = sha256(&sha256(payload.as_slice()));
+        let mut vec = Vec::with_capacity(msg.length());
+        vec.write_bytes(&[0; 20]);
+        vec.write_bytes(&hash[0..4]);
+        vec.write_bytes(payload.as_slice());
+        vec
+    });
+}
@@ -1,8 +1,11 @@
-mod msg;
+pub mod msg;
 
 const MAGIC_MAIN: u32 = 0xd9b4bef9;
 const MAGIC_TEST3: u32 = 0x0709110b;
 
+const BECH32_HRP_MAIN: &str = "bc";
+const BECH32_HRP_TEST3: &str = "tb";
+
 #[derive(Clone, Copy, Debug)]
 pub enum NetworkType {
     Main,
@@ -24,4 +27,21 @@ impl NetworkType {
             _ => None,
         }
     }
+
+    /// Human readable part of segwit addresses.
+    /// Use it with `bitcoinrs_bytes::bech32::{encode_segwit_address, decode_segwit_address}`.
+    pub fn bech32_hrp(&self) -> &'static str {
+        match *self {
+            NetworkType::Main => BECH32_HRP_MAIN,
+            NetworkType::Testnet3 => BECH32_HRP_TEST3,
+        }
+    }
+
+    pub fn from_bech32_hrp(hrp: &str) -> Option<NetworkType> {
+        match hrp {
+            BECH32_HRP_MAIN => Some(NetworkType::Main),
+            BECH32_HRP_TEST3 => Some(NetworkType::Testnet3),
+            _ => None,
+        }
+    }
 }
@@ -1,13 +1,16 @@
 #![allow(non_camel_case_types)]
 #![allow(dead_code)]
 
-use std::net::Ipv6Addr;
+use std::net::{IpAddr, Ipv6Addr};
 use std::time::{SystemTime, UNIX_EPOCH};
-use std::io::Cursor;
 
-use bitcoinrs_bytes::decode::{Decodable, DecodeError, ReadBuffer};
+use bitcoinrs_bytes::decode::{BorrowedReadBuffer, Decodable, DecodableBorrowed, DecodeError,
+                              DecodeErrorKind, ReadBuffer};
 use bitcoinrs_bytes::encode::{Encodable, EncodableSized, WriteBuffer};
-use bitcoinrs_bytes::endian::{u16_b, u16_l, u32_l, u64_l};
+use bitcoinrs_bytes::endian::{u16_b, u32_l, u64_l};
+use bitcoinrs_json::{field, FromJson, FromJsonError, ToJson, Value};
+
+pub use bitcoinrs_bytes::var::CompactSize;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Timestamp(u64);
@@ -37,57 +40,36 @@ impl Decodable for Timestamp {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct CompactSize(pub u64);
+/// Seconds since UNIX epoch.
+impl ToJson for Timestamp {
+    fn to_json(&self) -> Value {
+        Value::from(self.0)
+    }
+}
 
-impl Encodable for CompactSize {
-    fn length(&self) -> usize {
-        if self.0 < 0xFD {
-            1
-        } else if self.0 <= 0xFFFF {
-            3
-        } else if self.0 <= 0xFFFF_FFFF {
-            5
-        } else {
-            9
-        }
+impl FromJson for Timestamp {
+    fn from_json(value: &Value) -> Result<Timestamp, FromJsonError> {
+        u64::from_json(value).map(Timestamp)
     }
+}
 
-    fn encode<W: WriteBuffer>(&self, buf: &mut W) {
-        if self.0 < 0xFD {
-            buf.write(self.0 as u8);
-        } else if self.0 <= 0xFFFF {
-            buf.write(0xFD_u8);
-            buf.write(u16_l::new(self.0 as u16));
-        } else if self.0 <= 0xFFFF_FFFF {
-            buf.write(0xFE_u8);
-            buf.write(u32_l::new(self.0 as u32));
-        } else {
-            buf.write(0xFF_u8);
-            buf.write(u64_l::new(self.0));
-        }
+/// `NetAddr` carries only the lower 32 bits of a timestamp.
+impl From<Timestamp> for u32_l {
+    fn from(ts: Timestamp) -> u32_l {
+        u32_l::new(ts.0 as u32)
     }
 }
 
-impl Decodable for CompactSize {
-    fn decode<R: ReadBuffer>(bytes: &mut R) -> Result<CompactSize, DecodeError> {
-        let first = bytes.read::<u8>()?;
-        if first < 0xFD {
-            Ok(CompactSize(first as u64))
-        } else if first == 0xFD {
-            Ok(CompactSize(bytes.read::<u16_l>()?.value() as u64))
-        } else if first == 0xFE {
-            Ok(CompactSize(bytes.read::<u32_l>()?.value() as u64))
-        } else {
-            Ok(CompactSize(bytes.read::<u64_l>()?.value()))
-        }
+impl From<u32_l> for Timestamp {
+    fn from(ts: u32_l) -> Timestamp {
+        Timestamp(ts.value() as u64)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VarStr(pub String);
 
-impl<'a> Encodable for &'a VarStr {
+impl Encodable for VarStr {
     fn length(&self) -> usize {
         CompactSize(self.0.len() as u64).length() + self.0.len()
     }
@@ -99,13 +81,26 @@ impl<'a> Encodable for &'a VarStr {
 
 impl Decodable for VarStr {
     fn decode<R: ReadBuffer>(buf: &mut R) -> Result<VarStr, DecodeError> {
-        let len = buf.read::<CompactSize>()?.0;
-        let s = String::from_utf8(buf.read_bytes(len as usize)?.to_vec())
-            .map_err(|_| DecodeError::InvalidBytes)?;
+        let len = buf.context().check_alloc(buf.read::<CompactSize>()?.0)?;
+        let s = String::from_utf8(buf.read_bytes(len)?.to_vec())
+            .map_err(|_| DecodeErrorKind::InvalidUtf8)?;
         Ok(VarStr(s))
     }
 }
 
+/// Borrowed version of `VarStr`. Decoding it does not copy the string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VarStrRef<'a>(pub &'a str);
+
+impl<'a> DecodableBorrowed<'a> for VarStrRef<'a> {
+    fn decode_borrowed<R: BorrowedReadBuffer<'a>>(buf: &mut R) -> Result<Self, DecodeError> {
+        let len = buf.context().check_alloc(buf.read::<CompactSize>()?.0)?;
+        let s = ::std::str::from_utf8(buf.read_borrowed_bytes(len)?)
+            .map_err(|_| DecodeErrorKind::InvalidUtf8)?;
+        Ok(VarStrRef(s))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Service {
     Network = 1,
@@ -134,6 +129,44 @@ impl Services {
     pub fn add(&mut self, service: Service) {
         self.0 |= service as u64;
     }
+
+    pub fn contains(&self, service: Service) -> bool {
+        self.0 & service as u64 != 0
+    }
+
+    /// Names of known services, as `servicesnames` of Bitcoin Core's `getpeerinfo`.
+    pub fn names(&self) -> Vec<&'static str> {
+        let all = [
+            (Service::Network, "NETWORK"),
+            (Service::Getutxo, "GETUTXO"),
+            (Service::Bloom, "BLOOM"),
+            (Service::Witness, "WITNESS"),
+            (Service::NetworkLimited, "NETWORK_LIMITED"),
+        ];
+        all.iter()
+            .filter(|&&(service, _)| self.contains(service))
+            .map(|&(_, name)| name)
+            .collect()
+    }
+}
+
+/// 16 hex digits, as `services` of Bitcoin Core's `getpeerinfo`.
+impl ToJson for Services {
+    fn to_json(&self) -> Value {
+        Value::String(format!("{:016x}", self.0))
+    }
+}
+
+/// Both of hex string and number are accepted.
+impl FromJson for Services {
+    fn from_json(value: &Value) -> Result<Services, FromJsonError> {
+        match *value {
+            Value::String(ref s) => u64::from_str_radix(s, 16)
+                .map(Services)
+                .map_err(|_| FromJsonError::invalid_value(format!("{:?} is not hex", s))),
+            ref v => u64::from_json(v).map(Services),
+        }
+    }
 }
 
 impl EncodableSized for Services {
@@ -153,7 +186,7 @@ impl Decodable for Services {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct NetAddr {
-    ts: Timestamp, // Not present in version message.
+    ts: Timestamp, // Not present in version message. Encoded as u32.
     services: Services,
     ip: Ipv6Addr,
     port: u16,
@@ -170,38 +203,63 @@ impl NetAddr {
     }
 }
 
-impl EncodableSized for NetAddr {
-    const SIZE: usize = 30;
-    type Array = [u8; 30];
+impl_codec!(NetAddr {
+    ts as u32_l,
+    services,
+    ip,
+    port as u16_b,
+});
+
+/// Same as an entry of Bitcoin Core's `getnodeaddresses`.
+impl ToJson for NetAddr {
+    fn to_json(&self) -> Value {
+        Value::object(vec![
+            ("time", self.ts.to_json()),
+            // `getnodeaddresses` shows services as a number.
+            ("services", Value::from(self.services.0)),
+            ("address", ip_to_json(&self.ip)),
+            ("port", self.port.to_json()),
+            ("network", Value::from(network_name(&self.ip))),
+        ])
+    }
+}
 
-    #[allow(unused_must_use)]
-    fn bytes(&self) -> [u8; 30] {
-        let mut bytes = [0; 30];
-        {
-            let mut buf = Cursor::new(&mut bytes[..]);
+impl FromJson for NetAddr {
+    fn from_json(value: &Value) -> Result<NetAddr, FromJsonError> {
+        Ok(NetAddr {
+            ts: field(value, "time")?,
+            services: field(value, "services")?,
+            ip: ip_from_json(value)?,
+            port: field(value, "port")?,
+        })
+    }
+}
 
-            // never error
-            buf.write(self.ts);
-            buf.write(self.services);
-            buf.write(&self.ip.octets()[..]);
-            buf.write(u16_b::new(self.port));
+/// IPv4-mapped address is shown as IPv4.
+fn ip_to_json(ip: &Ipv6Addr) -> Value {
+    match ip.to_ipv4_mapped() {
+        Some(v4) => Value::String(v4.to_string()),
+        None => Value::String(ip.to_string()),
+    }
+}
+
+/// Read `address` member. IPv4 address is converted into IPv4-mapped address.
+fn ip_from_json(value: &Value) -> Result<Ipv6Addr, FromJsonError> {
+    let s: String = field(value, "address")?;
+    match s.parse::<IpAddr>() {
+        Ok(IpAddr::V4(v4)) => Ok(v4.to_ipv6_mapped()),
+        Ok(IpAddr::V6(v6)) => Ok(v6),
+        Err(_) => {
+            let msg = format!("{:?} is not an IP address", s);
+            Err(FromJsonError::invalid_value(msg).context("address"))
         }
-        bytes
     }
 }
 
-impl Decodable for NetAddr {
-    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<NetAddr, DecodeError> {
-        let ts = buf.read::<Timestamp>()?;
-        let services = buf.read::<Services>()?;
-        let ipv6 = Ipv6Addr::from(buf.read::<[u8; 16]>()?);
-        let port = buf.read::<u16_l>()?.value();
-        Ok(NetAddr {
-            ts: ts,
-            services: services,
-            ip: ipv6,
-            port: port,
-        })
+fn network_name(ip: &Ipv6Addr) -> &'static str {
+    match ip.to_ipv4_mapped() {
+        Some(_) => "ipv4",
+        None => "ipv6",
     }
 }
 
@@ -222,32 +280,88 @@ impl NetAddrForVersionMsg {
     }
 }
 
-impl EncodableSized for NetAddrForVersionMsg {
-    const SIZE: usize = 26;
-    type Array = [u8; 26];
-
-    #[allow(unused_must_use)]
-    fn bytes(&self) -> [u8; 26] {
-        let mut bytes = [0; 26];
-        {
-            let mut buf = Cursor::new(&mut bytes[..]);
-            buf.write(self.services);
-            buf.write(&self.ip.octets()[..]);
-            buf.write(u16_b::new(self.port));
-        }
-        bytes
+impl_codec!(NetAddrForVersionMsg {
+    services,
+    ip,
+    port as u16_b,
+});
+
+impl ToJson for NetAddrForVersionMsg {
+    fn to_json(&self) -> Value {
+        Value::object(vec![
+            ("services", self.services.to_json()),
+            ("address", ip_to_json(&self.ip)),
+            ("port", self.port.to_json()),
+        ])
     }
 }
 
-impl Decodable for NetAddrForVersionMsg {
-    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<NetAddrForVersionMsg, DecodeError> {
-        let services = buf.read::<Services>()?;
-        let ipv6 = Ipv6Addr::from(buf.read::<[u8; 16]>()?);
-        let port = buf.read::<u16_l>()?.value();
+impl FromJson for NetAddrForVersionMsg {
+    fn from_json(value: &Value) -> Result<NetAddrForVersionMsg, FromJsonError> {
         Ok(NetAddrForVersionMsg {
-            services: services,
-            ip: ipv6,
-            port: port,
+            services: field(value, "services")?,
+            ip: ip_from_json(value)?,
+            port: field(value, "port")?,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn net_addr_roundtrip() {
+        let addr = NetAddr::new(
+            Timestamp(1_500_000_000),
+            Services::new(&[Service::Network, Service::Witness]),
+            Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0x0a00, 0x0001),
+            8333,
+        );
+        let bytes = addr.to_vec();
+        assert_eq!(bytes.len(), 30);
+        assert_eq!(&bytes[28..], &[0x20, 0x8d]); // port is big endian
+
+        let decoded = Cursor::new(bytes.as_slice()).read::<NetAddr>().unwrap();
+        assert_eq!(decoded, addr);
+    }
+
+    #[test]
+    fn net_addr_json() {
+        let addr = NetAddr::new(
+            Timestamp(1_500_000_000),
+            Services::new(&[Service::Network, Service::Witness]),
+            Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0x0a00, 0x0001),
+            8333,
+        );
+        let json = addr.to_json().to_string();
+        assert_eq!(
+            json,
+            r#"{"time":1500000000,"services":9,"address":"10.0.0.1","port":8333,"network":"ipv4"}"#
+        );
+        assert_eq!(NetAddr::from_json_str(&json).unwrap(), addr);
+
+        let json = r#"{"time":0,"services":"0000000000000409","address":"::1","port":18333}"#;
+        let addr = NetAddr::from_json_str(json).unwrap();
+        assert_eq!(addr.ip, Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1));
+        assert_eq!(addr.services.names(), vec!["NETWORK", "WITNESS", "NETWORK_LIMITED"]);
+
+        let json = r#"{"time":0,"services":1,"address":"localhost","port":8333}"#;
+        let e = NetAddr::from_json_str(json).unwrap_err();
+        assert_eq!(e.path(), vec!["address"]);
+    }
+
+    #[test]
+    fn var_str_ref_borrows_input() {
+        let bytes = [3, b'a', b'b', b'c', 0xff];
+        let mut buf = Cursor::new(&bytes[..]);
+        let s = buf.read_borrowed::<VarStrRef>().unwrap();
+        assert_eq!(s, VarStrRef("abc"));
+        assert_eq!(s.0.as_ptr(), bytes[1..].as_ptr());
+
+        let invalid = [2, 0xc3, 0x28];
+        let e = Cursor::new(&invalid[..]).read_borrowed::<VarStrRef>().unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::InvalidUtf8);
+    }
+}
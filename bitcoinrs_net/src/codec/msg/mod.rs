@@ -6,12 +6,18 @@ pub use self::version::VersionMsgPayload;
 pub use self::verack::VerackMsgPayload;
 
 use bitcoinrs_bytes::encode::{Encodable, WriteBuffer};
-use bitcoinrs_bytes::decode::{Decodable, DecodeError, ReadBuffer};
+use bitcoinrs_bytes::decode::{BorrowedReadBuffer, Decodable, DecodableBorrowed, DecodeContext,
+                              DecodeError, DecodeErrorKind, ReadBuffer};
 use bitcoinrs_bytes::endian::u32_l;
-use bitcoinrs_crypto::sha256;
+use bitcoinrs_crypto::hash::Sha256d;
+use bitcoinrs_crypto::{sha256d, Sha256};
 
 use codec::NetworkType;
 
+/// Maximum length of payload accepted from peer, the same as Bitcoin Core's
+/// `MAX_PROTOCOL_MESSAGE_LENGTH`.
+pub const MAX_PAYLOAD_SIZE: u32 = 4 * 1000 * 1000;
+
 pub type VersionMsg = Msg<VersionMsgPayload>;
 pub type VerackMsg = Msg<VerackMsgPayload>;
 
@@ -47,8 +53,35 @@ impl<P: MsgPayload> Encodable for Msg<P> {
         24 + self.payload.length()
     }
 
-    #[allow(unused_must_use)]
+    /// Payload is encoded directly into `buf` and the checksum is filled in afterwards
+    /// if `buf` keeps the written message; see `WriteBuffer::keeps_written`.
+    /// Otherwise payload is encoded twice; into a `Sha256` engine to compute the checksum
+    /// first, and then into `buf`.
     fn encode<W: WriteBuffer>(&self, buf: &mut W) {
+        let payload_len = self.payload.length();
+        if !buf.keeps_written(24 + payload_len) {
+            let mut engine = Sha256::new();
+            self.payload.encode(&mut engine);
+            let hash = Sha256d::from_engine(engine);
+            let hash = hash.as_bytes();
+            self.encode_header(buf, payload_len, [hash[0], hash[1], hash[2], hash[3]]);
+            self.payload.encode(buf);
+            return;
+        }
+
+        self.encode_header(buf, payload_len, [0; 4]);
+        self.payload.encode(buf);
+
+        // Fill in checksum
+        let written = buf.written_mut(24 + payload_len)
+            .expect("WriteBuffer::keeps_written promised to keep written bytes");
+        let hash = sha256d(&written[24..]);
+        written[20..24].copy_from_slice(&hash[0..4]);
+    }
+}
+
+impl<P: MsgPayload> Msg<P> {
+    fn encode_header<W: WriteBuffer>(&self, buf: &mut W, payload_len: usize, checksum: [u8; 4]) {
         // Write magic valud.
         buf.write(u32_l::new(self.net_type.magic_num()));
 
@@ -56,55 +89,157 @@ impl<P: MsgPayload> Encodable for Msg<P> {
         buf.write_bytes(&P::COMMAND_BYTES);
 
         // Write length of payload in bytes
-        buf.write(u32_l::new(self.payload.length() as u32));
-
-        // Encode payload into bytes.
-        let payload = self.payload.to_vec();
-
-        // Compute and write checksum
-        let hash = sha256(&sha256(payload.as_slice()));
-        buf.write_bytes(&hash[0..4]);
+        buf.write(u32_l::new(payload_len as u32));
 
-        // Write payload
-        buf.write_bytes(&payload.as_slice());
+        buf.write_bytes(&checksum);
     }
 }
 
 impl<P: MsgPayload> Decodable for Msg<P> {
     fn decode<R: ReadBuffer>(buf: &mut R) -> Result<Self, DecodeError> {
-        // decode network type
-        let magic_num = buf.read::<u32_l>()?.value();
-        let net_type = NetworkType::from_magic_num(magic_num).ok_or(DecodeError::InvalidBytes)?;
+        let header = buf.read::<MsgHeader>()?;
+        if header.command != P::COMMAND_BYTES {
+            let kind = DecodeErrorKind::UnexpectedCommand(header.command);
+            return Err(DecodeError::new(kind).at(buf.offset() - 20).context("command"));
+        }
 
-        // read and check command bytes
-        {
-            let command = buf.read_bytes(12)?;
-            if command != P::COMMAND_BYTES {
-                return Err(DecodeError::InvalidBytes);
-            }
+        // read payload bytes
+        let ctx = network_context(buf.context());
+        let payload_offset = buf.offset();
+        let payload_bytes = buf.read_bytes(header.len as usize)
+            .map_err(|e| e.context("payload"))?;
+        header.check_checksum(payload_bytes, payload_offset)?;
+
+        // decode payload
+        let payload = ::std::io::Cursor::new(payload_bytes)
+            .with_context(ctx)
+            .read::<P>()
+            .map_err(|e| e.offset_by(payload_offset).context("payload"))?;
+
+        Ok(Msg::new(net_type_of(&header), payload))
+    }
+}
+
+/// Message whose payload is not decoded yet.
+/// Payload bytes are borrowed from the input buffer, so it is cheap even for a large block.
+#[derive(Debug, Clone, Copy)]
+pub struct RawMsg<'a> {
+    net_type: NetworkType,
+    command: [u8; 12],
+    payload: &'a [u8],
+}
+
+impl<'a> RawMsg<'a> {
+    pub fn net_type(&self) -> NetworkType {
+        self.net_type
+    }
+
+    /// NULL padded command string.
+    pub fn command(&self) -> &[u8; 12] {
+        &self.command
+    }
+
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    /// Decode payload as `P` in strict context. Fails if the command is not of `P`.
+    pub fn decode_payload<P: MsgPayload>(&self) -> Result<P, DecodeError> {
+        if self.command != P::COMMAND_BYTES {
+            let kind = DecodeErrorKind::UnexpectedCommand(self.command);
+            return Err(DecodeError::new(kind).context("command"));
         }
+        ::std::io::Cursor::new(self.payload)
+            .with_context(DecodeContext::strict())
+            .read::<P>()
+            .map_err(|e| e.context("payload"))
+    }
+}
 
-        // decode length of payload in bytes
-        let len = buf.read::<u32_l>()?.value();
-        println!("payload len : {:?}", len);
+impl<'a> DecodableBorrowed<'a> for RawMsg<'a> {
+    fn decode_borrowed<R: BorrowedReadBuffer<'a>>(buf: &mut R) -> Result<Self, DecodeError> {
+        let header = buf.read::<MsgHeader>()?;
 
-        // decode checksum
-        let checksum = buf.read::<[u8; 4]>()?;
-        println!("checksum : {:?}", checksum);
+        let payload_offset = buf.offset();
+        let payload = buf.read_borrowed_bytes(header.len as usize)
+            .map_err(|e| e.context("payload"))?;
+        header.check_checksum(payload, payload_offset)?;
 
-        // read payload bytes
-        let payload_bytes = buf.read_bytes(len as usize)?;
+        Ok(RawMsg {
+            net_type: net_type_of(&header),
+            command: header.command,
+            payload,
+        })
+    }
+}
 
-        // check checksum
-        let computed_hash = sha256(&sha256(payload_bytes));
-        // if &computed_hash[0..4] != checksum {
-            // return Err(DecodeError::InvalidBytes);
-        // }
+/// First 24 bytes of a message.
+struct MsgHeader {
+    magic: u32,
+    command: [u8; 12],
+    len: u32,
+    checksum: [u8; 4],
+}
 
-        // decode payload
-        let payload = P::decode(&mut ::std::io::Cursor::new(payload_bytes))?;
+impl MsgHeader {
+    /// `payload_offset` is used to locate an error.
+    fn check_checksum(&self, payload: &[u8], payload_offset: usize) -> Result<(), DecodeError> {
+        let computed_hash = sha256d(payload);
+        if computed_hash[0..4] != self.checksum {
+            let mut actual = [0; 4];
+            actual.copy_from_slice(&computed_hash[0..4]);
+            let kind = DecodeErrorKind::ChecksumMismatch {
+                expected: self.checksum,
+                actual,
+            };
+            return Err(DecodeError::new(kind).at(payload_offset - 4).context("checksum"));
+        }
+        Ok(())
+    }
+}
+
+/// Payload comes from a peer, so it is always decoded in strict context
+/// while other limits of `ctx` are kept.
+fn network_context(ctx: DecodeContext) -> DecodeContext {
+    DecodeContext { strict: true, ..ctx }
+}
+
+/// Magic number is already validated while decoding the header.
+fn net_type_of(header: &MsgHeader) -> NetworkType {
+    NetworkType::from_magic_num(header.magic).unwrap()
+}
+
+impl Decodable for MsgHeader {
+    fn decode<R: ReadBuffer>(buf: &mut R) -> Result<Self, DecodeError> {
+        let start = buf.offset();
+
+        // decode network type
+        let magic = buf.read_field::<u32_l>("magic")?.value();
+        if NetworkType::from_magic_num(magic).is_none() {
+            let kind = DecodeErrorKind::UnknownMagic(magic);
+            return Err(DecodeError::new(kind).at(start).context("magic"));
+        }
+
+        let command = buf.read_field::<[u8; 12]>("command")?;
+
+        // decode length of payload in bytes
+        let len = buf.read_field::<u32_l>("length")?.value();
+        if len > MAX_PAYLOAD_SIZE {
+            let kind = DecodeErrorKind::OversizeLength {
+                len: len as u64,
+                max: MAX_PAYLOAD_SIZE as u64,
+            };
+            return Err(DecodeError::new(kind).at(start + 16).context("length"));
+        }
 
-        Ok(Msg::new(net_type, payload))
+        let checksum = buf.read_field::<[u8; 4]>("checksum")?;
+
+        Ok(MsgHeader {
+            magic,
+            command,
+            len,
+            checksum,
+        })
     }
 }
 
@@ -115,3 +250,123 @@ pub trait MsgPayload: 'static + Sized + Encodable + Decodable {
         Msg::new(net_type, self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinrs_bytes::buffer::RingBuffer;
+    use bitcoinrs_bytes::encode::EncodableSized;
+    use bitcoinrs_bytes::stream::StreamWriter;
+    use std::io::Cursor;
+
+    #[test]
+    fn decode_error_location() {
+        let msg = VersionMsgPayload::new().into_msg(NetworkType::Main);
+        let mut bytes = msg.to_vec();
+        assert!(Cursor::new(bytes.as_slice()).read::<VersionMsg>().is_ok());
+
+        // Break checksum.
+        bytes[20] ^= 0xff;
+        let e = Cursor::new(bytes.as_slice()).read::<VersionMsg>().unwrap_err();
+        match e.kind() {
+            DecodeErrorKind::ChecksumMismatch { .. } => {}
+            kind => panic!("unexpected error kind {:?}", kind),
+        }
+        assert_eq!(e.offset(), Some(20));
+        assert_eq!(e.path(), vec!["checksum"]);
+
+        // Cut user agent in the middle.
+        let mut bytes = msg.to_vec();
+        let len = bytes.len();
+        bytes.truncate(len - 10);
+        let payload_len = (len - 10 - 24) as u32;
+        bytes[16..20].copy_from_slice(&u32_l::new(payload_len).bytes());
+        let checksum = sha256d(&bytes[24..]);
+        bytes[20..24].copy_from_slice(&checksum[0..4]);
+        let e = Cursor::new(bytes.as_slice()).read::<VersionMsg>().unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::ShortBuffer);
+        assert_eq!(e.offset(), Some(24 + 80 + 1));
+        assert_eq!(e.path(), vec!["payload", "user_agent"]);
+    }
+
+    #[test]
+    fn reject_non_canonical_compact_size() {
+        let msg = VersionMsgPayload::new().into_msg(NetworkType::Main);
+        let bytes = msg.to_vec();
+
+        // Encode length of user agent in 3 bytes instead of 1.
+        let ua_len = bytes[24 + 80];
+        let mut payload = bytes[24..24 + 80].to_vec();
+        payload.extend_from_slice(&[0xfd, ua_len, 0x00]);
+        payload.extend_from_slice(&bytes[24 + 81..]);
+        let mut bytes = bytes[..24].to_vec();
+        bytes[16..20].copy_from_slice(&u32_l::new(payload.len() as u32).bytes());
+        bytes[20..24].copy_from_slice(&sha256d(&payload)[0..4]);
+        bytes.extend_from_slice(&payload);
+
+        let e = Cursor::new(bytes.as_slice()).read::<VersionMsg>().unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::NonCanonicalCompactSize);
+        assert_eq!(e.offset(), Some(24 + 80));
+        assert_eq!(e.path(), vec!["payload", "user_agent"]);
+
+        let raw = Cursor::new(bytes.as_slice()).read_borrowed::<RawMsg>().unwrap();
+        let e = raw.decode_payload::<VersionMsgPayload>().unwrap_err();
+        assert_eq!(e.kind(), DecodeErrorKind::NonCanonicalCompactSize);
+    }
+
+    #[test]
+    fn encode_fills_checksum_in_place() {
+        let msg = VersionMsgPayload::new().into_msg(NetworkType::Main);
+        let payload = msg.payload().to_vec();
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&[0xf9, 0xbe, 0xb4, 0xd9]);
+        expected.extend_from_slice(b"version\0\0\0\0\0");
+        expected.extend_from_slice(&u32_l::new(payload.len() as u32).bytes());
+        expected.extend_from_slice(&sha256d(&payload)[0..4]);
+        expected.extend_from_slice(&payload);
+        assert_eq!(msg.to_vec(), expected);
+
+        let mut bytes = vec![0xAA; 3 + msg.length()];
+        {
+            let mut buf = Cursor::new(&mut bytes[..]);
+            buf.set_position(3);
+            msg.encode(&mut buf);
+        }
+        assert_eq!(&bytes[..3], &[0xAA; 3]);
+        assert_eq!(&bytes[3..], expected.as_slice());
+
+        // `StreamWriter` does not keep written bytes.
+        let mut buf = StreamWriter::new(Vec::new());
+        msg.encode(&mut buf);
+        assert_eq!(buf.finish().unwrap(), expected);
+
+        // `RingBuffer` keeps only unread bytes; still enough to fill the checksum.
+        let mut buf = RingBuffer::new(msg.length() + 10);
+        buf.write_bytes(&[0; 10]);
+        buf.drop_front(10);
+        msg.encode(&mut buf);
+        assert_eq!(buf.make_contiguous(), expected.as_slice());
+
+        // Message would wrap around the end of `RingBuffer`; checksum is computed first.
+        let mut buf = RingBuffer::new(msg.length() + 10);
+        buf.write_bytes(&[0xAA; 20]);
+        buf.drop_front(19);
+        assert!(!buf.keeps_written(msg.length()));
+        msg.encode(&mut buf);
+        assert_eq!(&buf.make_contiguous()[1..], expected.as_slice());
+    }
+
+    #[test]
+    fn raw_msg_borrows_payload() {
+        let msg = VersionMsgPayload::new().into_msg(NetworkType::Testnet3);
+        let bytes = msg.to_vec();
+
+        let raw = Cursor::new(bytes.as_slice()).read_borrowed::<RawMsg>().unwrap();
+        assert_eq!(raw.command(), &VersionMsgPayload::COMMAND_BYTES);
+        assert_eq!(raw.payload().as_ptr(), bytes[24..].as_ptr());
+        assert!(raw.decode_payload::<VersionMsgPayload>().is_ok());
+
+        let e = raw.decode_payload::<VerackMsgPayload>().unwrap_err();
+        assert_eq!(e.path(), vec!["command"]);
+    }
+}
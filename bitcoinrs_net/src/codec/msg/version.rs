@@ -1,8 +1,7 @@
-use std::net::Ipv6Addr;
+use std::net::Ipv4Addr;
 
-use bitcoinrs_bytes::decode::{Decodable, DecodeError, ReadBuffer};
-use bitcoinrs_bytes::encode::{Encodable, WriteBuffer};
 use bitcoinrs_bytes::endian::{i32_l, u64_l};
+use bitcoinrs_json::{field, FromJson, FromJsonError, ToJson, Value};
 
 use super::common_types::{NetAddrForVersionMsg, Service, Services, Timestamp, VarStr};
 use super::MsgPayload;
@@ -16,10 +15,8 @@ pub struct VersionMsgPayload {
     version: i32,
     services: Services,
     timestamp: Timestamp,
-    remote_ip: Ipv6Addr,
-    remote_port: u16,
-    local_ip: Ipv6Addr,
-    local_port: u16,
+    addr_recv: NetAddrForVersionMsg,
+    addr_from: NetAddrForVersionMsg,
     nonce: u64,
     user_agent: VarStr,
     start_height: i32,
@@ -34,23 +31,21 @@ impl VersionMsgPayload {
     /// - version : 70015
     /// - services : NODE_NETWORK
     /// - timestamp : [current timestamp]
-    /// - remote_ip : ::ffff:127:0.0.1
-    /// - remote_port : 8333
-    /// - local_ip : ::ffff:127:0:0:1
-    /// - local_port : 8333
+    /// - addr_recv : NODE_NETWORK, ::ffff:127.0.0.1, port 8331
+    /// - addr_from : NODE_NETWORK, ::ffff:127.0.0.1, port 8331
     /// - nonce : 0
     /// - user_agent : bitcoinrs
     /// - start_height : 0
     /// - relay : false
     pub fn new() -> VersionMsgPayload {
+        let default_services = Services::new(&[Service::Network]);
+        let localhost = Ipv4Addr::new(127, 0, 0, 1).to_ipv6_mapped();
         VersionMsgPayload {
             version: DEFAULT_VERSION,
-            services: Services::new(&[Service::Network]),
+            services: default_services,
             timestamp: Timestamp::now(),
-            remote_ip: Ipv6Addr::new(0, 0, 0, 0xffff, 127, 0, 0, 1),
-            remote_port: 8331,
-            local_ip: Ipv6Addr::new(0, 0, 0, 0xffff, 127, 0, 0, 1),
-            local_port: 8331,
+            addr_recv: NetAddrForVersionMsg::new(default_services, localhost, 8331),
+            addr_from: NetAddrForVersionMsg::new(default_services, localhost, 8331),
             nonce: 0, // If this value is 0, nonce field is ignored.
             user_agent: VarStr(DEFAULT_USER_AGENT.into()),
             start_height: 0,
@@ -63,11 +58,26 @@ impl VersionMsgPayload {
         self
     }
 
+    /// Services of the sending node.
+    /// Services embedded in `addr_recv` and `addr_from` are not changed;
+    /// use `set_addr_recv` and `set_addr_from` to set them.
     pub fn set_services(&mut self, services: Services) -> &mut Self {
         self.services = services;
         self
     }
 
+    /// Address of the receiving node as seen by the sending node.
+    pub fn set_addr_recv(&mut self, addr: NetAddrForVersionMsg) -> &mut Self {
+        self.addr_recv = addr;
+        self
+    }
+
+    /// Address of the sending node.
+    pub fn set_addr_from(&mut self, addr: NetAddrForVersionMsg) -> &mut Self {
+        self.addr_from = addr;
+        self
+    }
+
     pub fn set_timestamp(&mut self, timestamp: Timestamp) -> &mut Self {
         self.timestamp = timestamp;
         self
@@ -94,66 +104,50 @@ impl VersionMsgPayload {
     }
 }
 
-impl Encodable for VersionMsgPayload {
-    fn length(&self) -> usize {
-        4 // version
-        + 8 // services
-        + 8 // timestamp
-        + 26 // addr_recv
-        + 26 // addr_from
-        + 8 // nonce
-        + (&self.user_agent).length()
-        + 4 // start_height
-        + 1 // relay
-    }
-
-    fn encode<W: WriteBuffer>(&self, buf: &mut W) {
-        buf.write(i32_l::new(self.version));
-        buf.write(self.services);
-        buf.write(self.timestamp);
-        buf.write(NetAddrForVersionMsg::new(
-            self.services,
-            self.remote_ip,
-            self.remote_port,
-        ));
-        buf.write(NetAddrForVersionMsg::new(
-            self.services,
-            self.local_ip,
-            self.local_port,
-        ));
-        buf.write(u64_l::new(self.nonce));
-        buf.write(&self.user_agent);
-        buf.write(i32_l::new(self.start_height));
-        buf.write(self.relay as u8);
+impl_codec!(VersionMsgPayload {
+    version as i32_l,
+    services,
+    timestamp,
+    addr_recv,
+    addr_from,
+    nonce as u64_l,
+    user_agent,
+    start_height as i32_l,
+    relay,
+});
+
+/// Field names follow Bitcoin Core's `getpeerinfo` where they exist.
+impl ToJson for VersionMsgPayload {
+    fn to_json(&self) -> Value {
+        let names = self.services.names().into_iter().map(Value::from).collect();
+        Value::object(vec![
+            ("version", self.version.to_json()),
+            ("services", self.services.to_json()),
+            ("servicesnames", Value::Array(names)),
+            ("time", self.timestamp.to_json()),
+            ("addrrecv", self.addr_recv.to_json()),
+            ("addrfrom", self.addr_from.to_json()),
+            ("nonce", self.nonce.to_json()),
+            ("subver", self.user_agent.0.to_json()),
+            ("startingheight", self.start_height.to_json()),
+            ("relaytxes", self.relay.to_json()),
+        ])
     }
 }
 
-impl Decodable for VersionMsgPayload {
-    fn decode<R: ReadBuffer>(bytes: &mut R) -> Result<VersionMsgPayload, DecodeError> {
-        let version = bytes.read::<i32_l>()?.value();
-        let services = bytes.read::<Services>()?;
-        let timestamp = bytes.read::<Timestamp>()?;
-
-        let remote_addr = bytes.read::<NetAddrForVersionMsg>()?;
-        let local_addr = bytes.read::<NetAddrForVersionMsg>()?;
-
-        let nonce = bytes.read::<u64_l>()?.value();
-        let user_agent = bytes.read::<VarStr>()?;
-        let start_height = bytes.read::<i32_l>()?.value();
-        let relay = bytes.read::<u8>()? == 1;
-
+/// `servicesnames` is ignored.
+impl FromJson for VersionMsgPayload {
+    fn from_json(value: &Value) -> Result<VersionMsgPayload, FromJsonError> {
         Ok(VersionMsgPayload {
-            version: version,
-            services: services,
-            timestamp: timestamp,
-            remote_ip: remote_addr.ip,
-            remote_port: remote_addr.port,
-            local_ip: local_addr.ip,
-            local_port: local_addr.port,
-            nonce: nonce,
-            user_agent: user_agent,
-            start_height: start_height,
-            relay: relay,
+            version: field(value, "version")?,
+            services: field(value, "services")?,
+            timestamp: field(value, "time")?,
+            addr_recv: field(value, "addrrecv")?,
+            addr_from: field(value, "addrfrom")?,
+            nonce: field(value, "nonce")?,
+            user_agent: VarStr(field(value, "subver")?),
+            start_height: field(value, "startingheight")?,
+            relay: field(value, "relaytxes")?,
         })
     }
 }
@@ -161,3 +155,58 @@ impl Decodable for VersionMsgPayload {
 impl MsgPayload for VersionMsgPayload {
     const COMMAND_BYTES: [u8; 12] = [0x76, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e, 0, 0, 0, 0, 0];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoinrs_bytes::encode::Encodable;
+    use bitcoinrs_bytes::endian::u32_l;
+    use bitcoinrs_json::FromJsonErrorKind;
+
+    #[test]
+    fn version_json_roundtrip() {
+        let mut payload = VersionMsgPayload::new();
+        payload
+            .set_timestamp(Timestamp::from(u32_l::new(1_600_000_000)))
+            .set_nonce(u64::MAX)
+            .set_relay(true);
+
+        let json = payload.to_json();
+        assert_eq!(json.get("subver").unwrap().as_str(), Some("bitcoinrs"));
+        assert_eq!(json.get("services").unwrap().as_str(), Some("0000000000000001"));
+        assert_eq!(json.get("servicesnames").unwrap().to_string(), r#"["NETWORK"]"#);
+        assert_eq!(json.get("time").unwrap().to_string(), "1600000000");
+        assert_eq!(json.get("nonce").unwrap().to_string(), "18446744073709551615");
+
+        let decoded = VersionMsgPayload::from_json_str(&json.to_pretty_string(2)).unwrap();
+        assert_eq!(decoded.to_vec(), payload.to_vec());
+
+        let mut members = json.as_object().unwrap().to_vec();
+        members.retain(|m| m.0 != "startingheight");
+        let e = VersionMsgPayload::from_json(&Value::Object(members)).unwrap_err();
+        assert_eq!(e.kind(), &FromJsonErrorKind::MissingField);
+        assert_eq!(e.path(), vec!["startingheight"]);
+    }
+
+    #[test]
+    fn set_addresses() {
+        let mut payload = VersionMsgPayload::new();
+        let json = payload.to_json();
+        let addr_recv = json.get("addrrecv").unwrap();
+        assert_eq!(addr_recv.get("address").unwrap().as_str(), Some("127.0.0.1"));
+        assert_eq!(addr_recv.get("port").unwrap().to_string(), "8331");
+
+        let services = Services::new(&[Service::Network, Service::Witness]);
+        let ip = Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped();
+        payload
+            .set_services(services)
+            .set_addr_recv(NetAddrForVersionMsg::new(services, ip, 8333));
+
+        let json = payload.to_json();
+        let addr_recv = json.get("addrrecv").unwrap();
+        let addr_from = json.get("addrfrom").unwrap();
+        assert_eq!(addr_recv.get("address").unwrap().as_str(), Some("10.0.0.1"));
+        assert_eq!(addr_recv.get("services").unwrap().as_str(), Some("0000000000000009"));
+        assert_eq!(addr_from.get("services").unwrap().as_str(), Some("0000000000000001"));
+    }
+}
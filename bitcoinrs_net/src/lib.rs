@@ -1,4 +1,6 @@
+#[macro_use]
 extern crate bitcoinrs_bytes;
 extern crate bitcoinrs_crypto;
+extern crate bitcoinrs_json;
 
 pub mod codec;
@@ -1,7 +1,9 @@
 pub extern crate bitcoinrs_bytes;
 pub extern crate bitcoinrs_crypto;
 pub extern crate bitcoinrs_net;
+pub extern crate bitcoinrs_json;
 
 pub use bitcoinrs_net as net;
 pub use bitcoinrs_crypto as crypto;
 pub use bitcoinrs_bytes as bytes;
+pub use bitcoinrs_json as json;